@@ -1,4 +1,6 @@
-use dorazu_rs::{set_mouse_shake_callback, set_pasteboard_changed_callback, start_listening};
+use dorazu_rs::{set_mouse_shake_callback, set_pasteboard_changed_callback};
+#[cfg(any(target_os = "macos", all(target_os = "linux", feature = "x11")))]
+use dorazu_rs::start_listening;
 
 fn main() {
     // 注册拖拽粘贴板内容变化时的回调
@@ -17,7 +19,6 @@ fn main() {
                 dorazu_rs::model::drag_types::DragData::RemoteImage(content) => {
                     println!("拖拽远程图片: {:?}", content);
                 }
-                _ => println!("其他类型拖拽数据"),
             },
             None => println!("无效的拖拽数据"),
         }
@@ -34,5 +35,11 @@ fn main() {
     }));
 
     // 开始监听鼠标事件
-    start_listening();
+    #[cfg(any(target_os = "macos", all(target_os = "linux", feature = "x11")))]
+    match start_listening() {
+        Ok(listener) => listener.wait(),
+        Err(err) => eprintln!("无法开始监听: {err}"),
+    }
+    #[cfg(not(any(target_os = "macos", all(target_os = "linux", feature = "x11"))))]
+    eprintln!("当前平台没有默认的指针后端");
 }
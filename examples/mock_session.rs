@@ -0,0 +1,34 @@
+use dorazu_rs::mock::{MockPasteboard, MockPointerBackend};
+use dorazu_rs::model::drag_types::DragData;
+
+fn main() {
+    // 使用模拟粘贴板替换平台默认实现
+    let pasteboard = MockPasteboard::new();
+    dorazu_rs::set_drag_pasteboard(Box::new(pasteboard.clone()));
+
+    dorazu_rs::set_pasteboard_changed_callback(Box::new(|data| {
+        println!("拖拽内容变化: {:?}", data);
+    }));
+    dorazu_rs::set_mouse_shake_callback(Box::new(|_data| {
+        println!("检测到鼠标摇动!");
+    }));
+    dorazu_rs::set_drag_end_callback(Box::new(|data| {
+        println!("拖拽结束，数据：{:?}", data);
+    }));
+
+    // 模拟后端开始监听后立即返回，之后由脚本驱动事件
+    let backend = MockPointerBackend::new();
+    let _listener = dorazu_rs::start_listening_with(backend.clone()).expect("模拟后端不会启动失败");
+
+    backend.press(100.0, 100.0);
+    pasteboard.set_data(Some(DragData::PlainText("hello".to_owned())));
+    backend.drag_to(101.0, 100.0);
+
+    // 左右快速摇动
+    for i in 0..8 {
+        let x = if i % 2 == 0 { 160.0 } else { 100.0 };
+        backend.drag_to(x, 100.0);
+    }
+
+    backend.release(100.0, 100.0);
+}
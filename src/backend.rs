@@ -0,0 +1,246 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::model::pointer_event::{Point, PointerEvent};
+use crate::pasteboard::DragPasteboard;
+
+/// 指针事件回调类型，后端每采集到一个事件就调用一次
+///
+/// 拖拽粘贴板的变化计数在回调被调用时读取，无论事件之后是同步处理还是排队处理。
+/// 后端自己维护粘贴板时，应在回调返回后再更新变化计数。
+pub type PointerEventCallback = Arc<dyn Fn(PointerEvent) + Send + Sync + 'static>;
+
+/// 指针事件后端，负责从操作系统采集鼠标按下、拖动和抬起事件
+pub trait PointerBackend {
+    /// 开始监听指针事件并立即返回，通过返回的句柄停止或等待监听
+    fn start_listening(
+        &mut self,
+        callback: PointerEventCallback,
+    ) -> Result<ListenerHandle, ListenerError>;
+
+    /// 获取鼠标当前位置
+    fn current_location(&self) -> Point;
+
+    /// 与该后端配套的拖拽粘贴板，开始监听时替换实例当前使用的粘贴板
+    ///
+    /// 只能通过后端自己获得拖拽数据的实现（例如 Wayland 和回放）应返回它，默认返回 `None`，
+    /// 此时使用实例已有的粘贴板或当前平台默认的实现。
+    fn drag_pasteboard(&self) -> Option<Box<dyn DragPasteboard>> {
+        None
+    }
+}
+
+/// 启动监听失败的原因
+#[derive(Debug)]
+pub enum ListenerError {
+    /// 创建 CGEventTap 失败，通常是应用没有获得辅助功能权限
+    EventTapCreation,
+    /// 后端初始化失败，例如无法连接显示服务或打开输入设备
+    Backend(Box<dyn Error + Send + Sync>),
+    /// 监听线程在完成初始化前退出
+    ThreadExited,
+}
+
+impl fmt::Display for ListenerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListenerError::EventTapCreation => write!(f, "failed to create event tap"),
+            ListenerError::Backend(err) => write!(f, "failed to start pointer backend: {err}"),
+            ListenerError::ThreadExited => {
+                write!(f, "listener thread exited before the backend started")
+            }
+        }
+    }
+}
+
+impl Error for ListenerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ListenerError::Backend(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for ListenerError {
+    fn from(err: Box<dyn Error + Send + Sync>) -> Self {
+        ListenerError::Backend(err)
+    }
+}
+
+/// 由后端实现的监听器控制接口
+pub trait ListenerControl: Send + Sync {
+    /// 停止监听并释放系统资源，重复调用没有效果
+    fn stop(&self);
+
+    /// 阻塞当前线程直到监听结束
+    fn wait(&self);
+}
+
+/// 正在运行的监听器句柄，调用 [`stop`](ListenerHandle::stop) 或被丢弃时停止监听
+#[must_use = "丢弃句柄会立即停止监听"]
+pub struct ListenerHandle {
+    control: Arc<dyn ListenerControl>,
+}
+
+impl ListenerHandle {
+    pub fn new(control: impl ListenerControl + 'static) -> Self {
+        Self {
+            control: Arc::new(control),
+        }
+    }
+
+    /// 停止监听
+    pub fn stop(&self) {
+        self.control.stop();
+    }
+
+    /// 阻塞当前线程直到监听被停止或后端自行结束
+    ///
+    /// macOS 上需要在调用 `start_listening` 的线程中调用，此时会运行该线程的运行循环。
+    pub fn wait(&self) {
+        self.control.wait();
+    }
+}
+
+impl Drop for ListenerHandle {
+    fn drop(&mut self) {
+        self.control.stop();
+    }
+}
+
+/// 在独立线程中运行的监听器句柄，由 [`spawn_listener`](crate::spawn_listener) 创建，被丢弃时停止监听
+#[must_use = "丢弃句柄会立即停止监听"]
+pub struct SpawnedListener {
+    control: Arc<dyn ListenerControl>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SpawnedListener {
+    // 在新线程中启动后端，等待初始化完成后返回
+    pub(crate) fn spawn(
+        start: impl FnOnce() -> Result<ListenerHandle, ListenerError> + Send + 'static,
+    ) -> Result<Self, ListenerError> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("dorazu-listener".to_owned())
+            .spawn(move || match start() {
+                Ok(handle) => {
+                    let _ = tx.send(Ok(handle.control.clone()));
+                    // macOS 上在此运行本线程的运行循环
+                    handle.wait();
+                }
+                Err(err) => {
+                    let _ = tx.send(Err(err));
+                }
+            })
+            .map_err(|err| ListenerError::Backend(err.into()))?;
+
+        match rx.recv() {
+            Ok(Ok(control)) => Ok(Self {
+                control,
+                thread: Some(thread),
+            }),
+            Ok(Err(err)) => {
+                let _ = thread.join();
+                Err(err)
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(ListenerError::ThreadExited)
+            }
+        }
+    }
+
+    /// 停止监听，监听线程随后退出
+    pub fn stop(&self) {
+        self.control.stop();
+    }
+
+    /// 等待监听线程退出，监听被停止或后端自行结束时返回
+    pub fn join(mut self) -> thread::Result<()> {
+        match self.thread.take() {
+            Some(thread) => thread.join(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for SpawnedListener {
+    fn drop(&mut self) {
+        self.control.stop();
+    }
+}
+
+/// 可跨线程等待的停止标志，供后端实现监听线程的退出与等待
+#[derive(Default)]
+pub struct StopSignal {
+    stopped: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl StopSignal {
+    /// 标记为已停止，返回此前是否已经停止
+    pub fn stop(&self) -> bool {
+        let mut stopped = self.stopped.lock().unwrap();
+        let was_stopped = *stopped;
+        *stopped = true;
+        self.condvar.notify_all();
+        was_stopped
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        *self.stopped.lock().unwrap()
+    }
+
+    /// 阻塞直到停止
+    pub fn wait(&self) {
+        let stopped = self.stopped.lock().unwrap();
+        let _stopped = self
+            .condvar
+            .wait_while(stopped, |stopped| !*stopped)
+            .unwrap();
+    }
+
+    /// 最多等待指定时长，返回是否已停止
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let stopped = self.stopped.lock().unwrap();
+        let (stopped, _) = self
+            .condvar
+            .wait_timeout_while(stopped, timeout, |stopped| !*stopped)
+            .unwrap();
+        *stopped
+    }
+}
+
+/// 在后台线程中运行的监听器，停止时设置标志并调用 `wake` 唤醒阻塞中的线程
+///
+/// 监听线程应在每次被唤醒后检查标志，自行结束时也应调用 [`StopSignal::stop`]。
+pub struct ThreadListener {
+    signal: Arc<StopSignal>,
+    wake: Box<dyn Fn() + Send + Sync>,
+}
+
+impl ThreadListener {
+    pub fn new(signal: Arc<StopSignal>, wake: impl Fn() + Send + Sync + 'static) -> Self {
+        Self {
+            signal,
+            wake: Box::new(wake),
+        }
+    }
+}
+
+impl ListenerControl for ThreadListener {
+    fn stop(&self) {
+        if !self.signal.stop() {
+            (self.wake)();
+        }
+    }
+
+    fn wait(&self) {
+        self.signal.wait();
+    }
+}
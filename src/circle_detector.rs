@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+use crate::gesture::GestureRecognizer;
+use crate::model::gesture_event::{CircleDirection, GestureDetail};
+use crate::model::pointer_event::Point;
+
+/// 画圈识别的阈值配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircleConfig {
+    /// 保留鼠标位置历史的时长，需要在这段时间内画完一圈
+    pub history_window: Duration,
+    /// 判定为画圈所需的累计转角（弧度），默认为一整圈
+    pub min_turning_angle: f64,
+    /// 圈的最小半径（像素），更小的圈视为抖动
+    pub min_radius: f64,
+    /// 相邻两个采样点之间的最小距离（像素），更近的位置被忽略
+    pub min_step: f64,
+    /// 路径终点到起点的最大距离与半径之比，路径需要回到起点附近才算画完一圈
+    pub max_closure_ratio: f64,
+    /// 路径外接矩形短边与长边之比的下限，来回摇动形成的细长路径不算画圈
+    pub min_aspect_ratio: f64,
+    /// 重复画圈的冷却时间，为 `None` 时每次拖拽只上报一次
+    pub repeat_cooldown: Option<Duration>,
+}
+
+impl Default for CircleConfig {
+    fn default() -> Self {
+        Self {
+            history_window: Duration::from_millis(1500),
+            min_turning_angle: TAU,
+            min_radius: 20.0,
+            min_step: 3.0,
+            max_closure_ratio: 0.5,
+            min_aspect_ratio: 0.5,
+            repeat_cooldown: None,
+        }
+    }
+}
+
+/// 根据累计转角识别拖拽中画圈的手势识别器
+///
+/// 历史窗口内相邻线段之间的转角依次累加，绝对值达到阈值、路径回到起点附近且外接矩形
+/// 不过于细长时判定为画了一圈，半径取采样点到其重心的平均距离。
+/// 与摇动相同，每次拖拽默认只上报一次。
+pub struct CircleDetector {
+    // 检测阈值
+    config: CircleConfig,
+    // 最近的采样点及其时间戳
+    position_history: VecDeque<(Point, Duration)>,
+    // 当前拖拽中已经上报的次数
+    circles_in_current_drag: usize,
+    // 上一次上报的时间，用于计算冷却时间
+    last_reported: Option<Duration>,
+}
+
+impl CircleDetector {
+    pub fn new(config: CircleConfig) -> Self {
+        Self {
+            config,
+            position_history: VecDeque::new(),
+            circles_in_current_drag: 0,
+            last_reported: None,
+        }
+    }
+
+    // 获取检测阈值
+    pub fn config(&self) -> CircleConfig {
+        self.config
+    }
+
+    // 替换检测阈值
+    pub fn set_config(&mut self, config: CircleConfig) {
+        self.config = config;
+    }
+
+    // 按照每次拖拽的上报规则判断当前是否允许上报
+    fn can_report(&self, now: Duration) -> bool {
+        if self.circles_in_current_drag == 0 {
+            return true;
+        }
+        match (self.config.repeat_cooldown, self.last_reported) {
+            (Some(cooldown), Some(reported)) => now.saturating_sub(reported) >= cooldown,
+            _ => false,
+        }
+    }
+
+    // 累加历史中相邻线段的有向转角，在屏幕坐标系（y 轴向下）中顺时针为正
+    fn turning_angle(&self) -> f64 {
+        let points: Vec<Point> = self.position_history.iter().map(|(point, _)| *point).collect();
+        points
+            .windows(3)
+            .map(|window| {
+                let (ax, ay) = (window[1].x - window[0].x, window[1].y - window[0].y);
+                let (bx, by) = (window[2].x - window[1].x, window[2].y - window[1].y);
+                (ax * by - ay * bx).atan2(ax * bx + ay * by)
+            })
+            .sum()
+    }
+
+    // 采样点到其重心的平均距离
+    fn radius(&self) -> f64 {
+        let count = self.position_history.len() as f64;
+        let (sum_x, sum_y) = self
+            .position_history
+            .iter()
+            .fold((0.0, 0.0), |(x, y), (point, _)| (x + point.x, y + point.y));
+        let (center_x, center_y) = (sum_x / count, sum_y / count);
+        self.position_history
+            .iter()
+            .map(|(point, _)| (point.x - center_x).hypot(point.y - center_y))
+            .sum::<f64>()
+            / count
+    }
+
+    // 路径终点到起点的距离
+    fn closure_distance(&self) -> f64 {
+        match (self.position_history.front(), self.position_history.back()) {
+            (Some((first, _)), Some((last, _))) => (last.x - first.x).hypot(last.y - first.y),
+            _ => 0.0,
+        }
+    }
+
+    // 采样点外接矩形短边与长边之比
+    fn aspect_ratio(&self) -> f64 {
+        let (min_x, max_x, min_y, max_y) = self.position_history.iter().fold(
+            (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY),
+            |(min_x, max_x, min_y, max_y), (point, _)| {
+                (min_x.min(point.x), max_x.max(point.x), min_y.min(point.y), max_y.max(point.y))
+            },
+        );
+        let (width, height) = (max_x - min_x, max_y - min_y);
+        if width.max(height) > 0.0 {
+            width.min(height) / width.max(height)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl GestureRecognizer for CircleDetector {
+    fn reset(&mut self) {
+        self.position_history.clear();
+        self.circles_in_current_drag = 0;
+        self.last_reported = None;
+    }
+
+    fn add_position(&mut self, location: Point, timestamp: Duration) -> Option<GestureDetail> {
+        // 忽略离上一个采样点太近的位置，避免抖动产生很大的转角
+        if let Some((last, _)) = self.position_history.back()
+            && (location.x - last.x).hypot(location.y - last.y) < self.config.min_step
+        {
+            return None;
+        }
+        self.position_history.push_back((location, timestamp));
+        while let Some((_, oldest)) = self.position_history.front() {
+            if timestamp.saturating_sub(*oldest) > self.config.history_window {
+                self.position_history.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let angle = self.turning_angle();
+        if angle.abs() < self.config.min_turning_angle {
+            return None;
+        }
+        let radius = self.radius();
+        if radius < self.config.min_radius || !self.can_report(timestamp) {
+            return None;
+        }
+        // 沿细长路径来回摇动时每次折返都朝同一方向转半圈，同样能累计出一整圈的转角
+        if self.closure_distance() > self.config.max_closure_ratio * radius
+            || self.aspect_ratio() < self.config.min_aspect_ratio
+        {
+            return None;
+        }
+
+        // 从下一个采样点重新累计，同一圈不会重复上报
+        self.position_history.clear();
+        self.circles_in_current_drag += 1;
+        self.last_reported = Some(timestamp);
+        Some(GestureDetail::Circle {
+            ordinal: self.circles_in_current_drag,
+            direction: if angle > 0.0 {
+                CircleDirection::Clockwise
+            } else {
+                CircleDirection::CounterClockwise
+            },
+            radius,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 依次输入路径点，相邻点间隔 10 毫秒，返回全部识别结果及其时间
+    fn feed(detector: &mut CircleDetector, path: &[Point]) -> Vec<(u64, GestureDetail)> {
+        path.iter()
+            .enumerate()
+            .filter_map(|(index, &point)| {
+                let ms = index as u64 * 10;
+                let detail = detector.add_position(point, Duration::from_millis(ms))?;
+                Some((ms, detail))
+            })
+            .collect()
+    }
+
+    // 以 (100, 100) 为圆心画 `turns` 圈，半径从 `start_radius` 线性变化到 `end_radius`，
+    // 每圈 36 个点；`clockwise` 指屏幕上看到的方向（y 轴向下）
+    fn spiral(turns: f64, start_radius: f64, end_radius: f64, clockwise: bool) -> Vec<Point> {
+        let count = (turns * 36.0).round() as usize;
+        (0..=count)
+            .map(|index| {
+                let t = index as f64 / count as f64;
+                let angle = t * turns * TAU * if clockwise { 1.0 } else { -1.0 };
+                let radius = start_radius + t * (end_radius - start_radius);
+                Point {
+                    x: 100.0 + radius * angle.cos(),
+                    y: 100.0 + radius * angle.sin(),
+                }
+            })
+            .collect()
+    }
+
+    fn circle(detail: &GestureDetail) -> (usize, CircleDirection, f64) {
+        match *detail {
+            GestureDetail::Circle {
+                ordinal,
+                direction,
+                radius,
+            } => (ordinal, direction, radius),
+            ref other => panic!("unexpected gesture {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reports_direction_and_radius() {
+        for (clockwise, expected) in [
+            (true, CircleDirection::Clockwise),
+            (false, CircleDirection::CounterClockwise),
+        ] {
+            let mut detector = CircleDetector::new(CircleConfig::default());
+            let results = feed(&mut detector, &spiral(1.1, 40.0, 40.0, clockwise));
+            assert_eq!(results.len(), 1);
+            let (ordinal, direction, radius) = circle(&results[0].1);
+            assert_eq!((ordinal, direction), (1, expected));
+            assert!((radius - 40.0).abs() < 2.0, "radius {radius}");
+        }
+    }
+
+    #[test]
+    fn small_circle_is_ignored() {
+        let mut detector = CircleDetector::new(CircleConfig::default());
+        assert!(feed(&mut detector, &spiral(1.1, 10.0, 10.0, true)).is_empty());
+    }
+
+    #[test]
+    fn open_arc_beyond_full_turn_is_not_a_circle() {
+        let mut detector = CircleDetector::new(CircleConfig::default());
+        // 转过一圈多，但半径不断变大，终点离起点很远
+        assert!(feed(&mut detector, &spiral(1.3, 25.0, 110.0, true)).is_empty());
+    }
+
+    #[test]
+    fn back_and_forth_shake_is_not_a_circle() {
+        let mut detector = CircleDetector::new(CircleConfig::default());
+        // 沿 y = 3 向右、沿 y = -3 向左，每次折返都朝同一方向转半圈
+        let path: Vec<Point> = (0..6)
+            .flat_map(|pass| {
+                (0..=10).map(move |step| {
+                    let t = step as f64 / 10.0;
+                    let (x, y) = if pass % 2 == 0 {
+                        (100.0 * t, 3.0)
+                    } else {
+                        (100.0 * (1.0 - t), -3.0)
+                    };
+                    Point { x, y }
+                })
+            })
+            .collect();
+        assert!(feed(&mut detector, &path).is_empty());
+    }
+
+    #[test]
+    fn reports_once_per_drag_without_cooldown() {
+        let mut detector = CircleDetector::new(CircleConfig::default());
+        let path = spiral(3.0, 40.0, 40.0, true);
+        assert_eq!(feed(&mut detector, &path).len(), 1);
+
+        // 重置后开始新的拖拽，序号重新从 1 开始
+        detector.reset();
+        let results = feed(&mut detector, &path);
+        assert_eq!(results.len(), 1);
+        assert_eq!(circle(&results[0].1).0, 1);
+    }
+
+    #[test]
+    fn repeat_cooldown_limits_reports() {
+        let mut detector = CircleDetector::new(CircleConfig {
+            repeat_cooldown: Some(Duration::from_millis(500)),
+            ..CircleConfig::default()
+        });
+        // 每圈 360 毫秒，第二圈画完时还在冷却时间内，第三圈画完时已经过了冷却时间
+        let results = feed(&mut detector, &spiral(3.0, 40.0, 40.0, true));
+        let reports: Vec<(u64, usize)> = results
+            .iter()
+            .map(|(ms, detail)| (*ms, circle(detail).0))
+            .collect();
+        assert_eq!(reports.len(), 2, "{reports:?}");
+        assert_eq!(reports[0].1, 1);
+        assert_eq!(reports[1].1, 2);
+        assert!(reports[1].0 - reports[0].0 >= 500);
+    }
+}
@@ -0,0 +1,218 @@
+use std::time::{Duration, Instant};
+
+/// 时间来源，返回从某个固定起点开始经过的时长
+///
+/// 指针事件不带时间戳时，拖拽状态以它的读数作为事件的时间。
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+/// 基于 [`Instant`] 的单调时钟，以创建时刻为起点
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock {
+    origin: Instant,
+}
+
+impl MonotonicClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// 手动推进的时钟，用于在测试中精确控制时间
+#[cfg(any(test, feature = "testing"))]
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: std::sync::Mutex<Duration>,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl ManualClock {
+    /// 创建读数为零的时钟
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 向前推进指定时长
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+
+    /// 直接设置读数
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::Dorazu;
+    use crate::event_queue::EventDelivery;
+    use crate::mock::{MockPasteboard, MockPointerBackend};
+    use crate::model::shake_event::ShakeInfo;
+    use crate::shake_config::ShakeConfig;
+
+    // 以手动时钟驱动的实例，模拟后端发送的事件都不带时间戳
+    struct Session {
+        clock: Arc<ManualClock>,
+        backend: MockPointerBackend,
+        pasteboard: MockPasteboard,
+        log: Arc<Mutex<Vec<String>>>,
+        shakes: Arc<Mutex<Vec<ShakeInfo>>>,
+        _dorazu: Dorazu,
+        _listener: crate::backend::ListenerHandle,
+    }
+
+    impl Session {
+        fn new() -> Self {
+            let clock = Arc::new(ManualClock::new());
+            let pasteboard = MockPasteboard::new();
+            let dorazu = Dorazu::builder()
+                .pasteboard(Box::new(pasteboard.clone()))
+                .event_delivery(EventDelivery::Synchronous)
+                .clock(clock.clone())
+                .build();
+            let log = Arc::new(Mutex::new(Vec::new()));
+            let shakes = Arc::new(Mutex::new(Vec::new()));
+            {
+                let (log, shakes) = (log.clone(), shakes.clone());
+                dorazu.subscribe_shake(Box::new(move |event| {
+                    log.lock().unwrap().push(format!("shake {}", event.ordinal));
+                    shakes.lock().unwrap().push(event.info);
+                }));
+            }
+            {
+                let log = log.clone();
+                dorazu.subscribe_shake_ended(Box::new(move |event| {
+                    log.lock()
+                        .unwrap()
+                        .push(format!("shake ended {}", event.ordinal));
+                }));
+            }
+            {
+                let log = log.clone();
+                dorazu
+                    .subscribe_drag_end(Box::new(move |_| log.lock().unwrap().push("end".into())));
+            }
+            let backend = MockPointerBackend::new();
+            let listener = dorazu.start_listening_with(backend.clone()).unwrap();
+            Self {
+                clock,
+                backend,
+                pasteboard,
+                log,
+                shakes,
+                _dorazu: dorazu,
+                _listener: listener,
+            }
+        }
+
+        // 按下并开始拖拽新内容
+        fn start_drag(&self) {
+            self.backend.press(0.0, 0.0);
+            self.pasteboard.clear();
+        }
+
+        // 推进时钟后拖动到指定位置
+        fn drag_after(&self, ms: u64, x: f64) {
+            self.clock.advance(Duration::from_millis(ms));
+            self.backend.drag_to(x, 0.0);
+        }
+
+        // 每隔 `interval_ms` 在 0 和 50 之间来回移动
+        fn wiggle(&self, count: usize, interval_ms: u64) {
+            for index in 0..count {
+                self.drag_after(interval_ms, if index % 2 == 0 { 50.0 } else { 0.0 });
+            }
+        }
+
+        fn log(&self) -> Vec<String> {
+            self.log.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn reversal_spacing_is_measured_against_change_interval() {
+        let interval = ShakeConfig::DEFAULT.change_interval.as_millis() as u64;
+
+        // 折返间隔略短于 200 毫秒时判定为摇动
+        let session = Session::new();
+        session.start_drag();
+        session.wiggle(8, interval - 10);
+        assert_eq!(session.log(), ["shake 1"]);
+
+        // 折返间隔略长于 200 毫秒时每次都重新计数
+        let session = Session::new();
+        session.start_drag();
+        session.wiggle(20, interval + 10);
+        assert!(session.log().is_empty());
+    }
+
+    #[test]
+    fn shake_ends_once_clock_passes_change_interval() {
+        let interval = ShakeConfig::DEFAULT.change_interval.as_millis() as u64;
+        let session = Session::new();
+        session.start_drag();
+        session.wiggle(8, 20);
+        assert_eq!(session.log(), ["shake 1"]);
+
+        // 沿最后的方向继续移动，不再折返，还没超过间隔时摇动仍在持续
+        session.drag_after(interval - 30, -10.0);
+        assert_eq!(session.log(), ["shake 1"]);
+
+        // 时钟越过间隔后的下一个事件上报摇动停止
+        session.drag_after(40, -20.0);
+        assert_eq!(session.log(), ["shake 1", "shake ended 1"]);
+
+        session.clock.advance(Duration::from_millis(20));
+        session.backend.release(-20.0, 0.0);
+        assert_eq!(session.log(), ["shake 1", "shake ended 1", "end"]);
+    }
+
+    #[test]
+    fn positions_older_than_history_window_are_forgotten() {
+        let window = ShakeConfig::DEFAULT.history_window.as_millis() as u64;
+        let peak_velocity = |pause_ms: u64| {
+            let session = Session::new();
+            session.start_drag();
+            // 先快速移动 200 像素，停顿之后再以每 20 毫秒 50 像素的速度摇动
+            session.drag_after(10, -200.0);
+            session.drag_after(10, 0.0);
+            session.clock.advance(Duration::from_millis(pause_ms));
+            session.wiggle(8, 20);
+            let shakes = session.shakes.lock().unwrap();
+            assert_eq!(shakes.len(), 1);
+            shakes[0].peak_velocity
+        };
+
+        // 停顿短于历史窗口时，快速移动仍在历史中
+        assert!(peak_velocity(window / 2) >= 20_000.0);
+        // 停顿超过历史窗口后只剩摇动本身的速度
+        let velocity = peak_velocity(window + 100);
+        assert!((velocity - 2_500.0).abs() < 1.0, "peak velocity {velocity}");
+    }
+}
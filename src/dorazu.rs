@@ -0,0 +1,597 @@
+use std::error::Error;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::backend::{ListenerError, ListenerHandle, PointerBackend, SpawnedListener};
+use crate::clock::{Clock, MonotonicClock};
+use crate::drag_state::{DragState, ShakeChange};
+use crate::event_queue::{self, EventDelivery};
+use crate::gesture::{GestureRecognizer, SHAKE_GESTURE};
+use crate::model::drag_types::DragData;
+use crate::model::gesture_event::{GestureDetail, GestureEvent};
+use crate::model::pointer_event::{MouseButton, PointerEvent};
+use crate::model::shake_event::{ShakeAxis, ShakeEndedEvent, ShakeEvent, ShakeInfo};
+use crate::pasteboard::{self, DragPasteboard};
+use crate::shake_config::ShakeConfig;
+use crate::subscription::{SubscriptionId, Subscribers};
+use crate::{
+    DragEndCallback, GestureCallback, MouseShakeCallback, PasteboardChangedCallback,
+    ShakeCallback, ShakeEndedCallback,
+};
+
+// 订阅者内部保存的回调类型
+type DragDataFn = dyn Fn(Option<DragData>) + Send + Sync + 'static;
+type ShakeFn = dyn Fn(ShakeEvent) + Send + Sync + 'static;
+type ShakeEndedFn = dyn Fn(ShakeEndedEvent) + Send + Sync + 'static;
+type GestureFn = dyn Fn(GestureEvent) + Send + Sync + 'static;
+
+// 各类事件的订阅者
+#[derive(Default)]
+struct Subscriptions {
+    pasteboard_changed: Subscribers<DragDataFn>,
+    mouse_shake: Subscribers<ShakeFn>,
+    shake_ended: Subscribers<ShakeEndedFn>,
+    gesture: Subscribers<GestureFn>,
+    drag_end: Subscribers<DragDataFn>,
+}
+
+/// 拖拽检测器实例，拥有各自的拖拽状态、配置和订阅者
+///
+/// 克隆得到的是同一个实例的句柄。crate 根部的自由函数操作一个全局的默认实例。
+#[derive(Clone)]
+pub struct Dorazu {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// 拖拽粘贴板，后端回调中读取变化计数时不需要等待拖拽状态的锁
+    ///
+    /// 为 `None` 时使用当前平台默认的实现，在第一次使用时才打开。
+    pasteboard: RwLock<Option<Arc<dyn DragPasteboard>>>,
+    /// 最近一次读取的拖拽数据及读取时的变化计数，计数不变时不再重复读取
+    data_cache: Mutex<Option<(i64, Option<DragData>)>>,
+    /// 拖拽过程中的状态
+    state: Mutex<DragState>,
+    /// 事件订阅者
+    subscriptions: Mutex<Subscriptions>,
+    /// 指针事件的投递方式
+    delivery: Mutex<EventDelivery>,
+    /// 因事件队列已满而被丢弃的事件数
+    dropped_events: Arc<AtomicU64>,
+    /// 正在进行的录制，只记录该实例处理的事件
+    #[cfg(feature = "trace")]
+    recorder: Mutex<Option<crate::trace::TraceRecorder>>,
+    /// 与拖拽状态共用的时钟，录制时以它计算记录的时间
+    #[cfg(feature = "trace")]
+    clock: Arc<dyn Clock>,
+}
+
+/// [`Dorazu`] 的构建器，由 [`Dorazu::builder`] 创建
+#[derive(Default)]
+pub struct DorazuBuilder {
+    pasteboard: Option<Box<dyn DragPasteboard>>,
+    delivery: EventDelivery,
+    shake_config: ShakeConfig,
+    clock: Option<Arc<dyn Clock>>,
+    recognizers: Vec<(String, Box<dyn GestureRecognizer>)>,
+}
+
+impl DorazuBuilder {
+    /// 使用指定的拖拽粘贴板，默认使用当前平台的实现，在开始监听时才打开
+    pub fn pasteboard(mut self, pasteboard: Box<dyn DragPasteboard>) -> Self {
+        self.pasteboard = Some(pasteboard);
+        self
+    }
+
+    /// 设置指针事件的投递方式，默认使用有界队列
+    pub fn event_delivery(mut self, delivery: EventDelivery) -> Self {
+        self.delivery = delivery;
+        self
+    }
+
+    /// 设置摇动检测阈值，默认使用 [`ShakeConfig::DEFAULT`]
+    pub fn shake_config(mut self, config: ShakeConfig) -> Self {
+        self.shake_config = config;
+        self
+    }
+
+    /// 设置指针事件不带时间戳时使用的时钟，默认使用 [`MonotonicClock`]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// 注册额外的手势识别器，识别结果以指定名称的手势事件上报
+    ///
+    /// 名称与内置摇动的 [`SHAKE_GESTURE`] 相同的识别器被忽略。
+    pub fn recognizer(mut self, name: &str, recognizer: Box<dyn GestureRecognizer>) -> Self {
+        self.recognizers.push((name.to_owned(), recognizer));
+        self
+    }
+
+    pub fn build(self) -> Dorazu {
+        let clock = self
+            .clock
+            .unwrap_or_else(|| Arc::new(MonotonicClock::new()));
+        let mut state = DragState::new(self.shake_config, clock.clone());
+        for (name, recognizer) in self.recognizers {
+            // 与内置摇动同名的识别器不注册
+            let _ = state.add_recognizer(name, recognizer);
+        }
+        Dorazu {
+            inner: Arc::new(Inner {
+                pasteboard: RwLock::new(self.pasteboard.map(Arc::from)),
+                data_cache: Mutex::new(None),
+                state: Mutex::new(state),
+                subscriptions: Mutex::new(Subscriptions::default()),
+                delivery: Mutex::new(self.delivery),
+                dropped_events: Arc::new(AtomicU64::new(0)),
+                #[cfg(feature = "trace")]
+                recorder: Mutex::new(None),
+                #[cfg(feature = "trace")]
+                clock,
+            }),
+        }
+    }
+}
+
+impl Dorazu {
+    pub fn builder() -> DorazuBuilder {
+        DorazuBuilder::default()
+    }
+
+    /// 设置拖拽粘贴板内容变化的回调函数，替换上一次设置的回调，不影响其他订阅
+    pub fn set_pasteboard_changed_callback(&self, cb: PasteboardChangedCallback) {
+        self.subscriptions().pasteboard_changed.set(cb.into());
+    }
+
+    /// 设置鼠标摇动检测的回调函数，替换上一次设置的回调，不影响其他订阅
+    pub fn set_mouse_shake_callback(&self, cb: MouseShakeCallback) {
+        self.subscriptions().mouse_shake.set(Arc::new(move |event: ShakeEvent| cb(event.data)));
+    }
+
+    /// 设置拖拽结束回调函数，替换上一次设置的回调，不影响其他订阅
+    pub fn set_drag_end_callback(&self, cb: DragEndCallback) {
+        self.subscriptions().drag_end.set(cb.into());
+    }
+
+    /// 订阅拖拽粘贴板内容变化，可以有多个订阅者
+    pub fn subscribe_pasteboard_changed(&self, cb: PasteboardChangedCallback) -> SubscriptionId {
+        self.subscriptions().pasteboard_changed.subscribe(cb.into())
+    }
+
+    /// 订阅鼠标摇动，可以有多个订阅者
+    pub fn subscribe_mouse_shake(&self, cb: MouseShakeCallback) -> SubscriptionId {
+        self.subscribe_shake(Box::new(move |event| cb(event.data)))
+    }
+
+    /// 订阅鼠标摇动，回调可以得到摇动方向等信息
+    pub fn subscribe_shake(&self, cb: ShakeCallback) -> SubscriptionId {
+        self.subscriptions().mouse_shake.subscribe(cb.into())
+    }
+
+    /// 只订阅指定方向的鼠标摇动
+    pub fn subscribe_shake_axis(&self, axis: ShakeAxis, cb: ShakeCallback) -> SubscriptionId {
+        self.subscribe_shake(Box::new(move |event| {
+            if event.axis == axis {
+                cb(event);
+            }
+        }))
+    }
+
+    /// 订阅摇动停止，在下一次指针事件或拖拽结束时发现摇动已经停止后上报
+    pub fn subscribe_shake_ended(&self, cb: ShakeEndedCallback) -> SubscriptionId {
+        self.subscriptions().shake_ended.subscribe(cb.into())
+    }
+
+    /// 订阅全部手势事件，包括内置的摇动和额外注册的识别器
+    pub fn subscribe_gesture(&self, cb: GestureCallback) -> SubscriptionId {
+        self.subscriptions().gesture.subscribe(cb.into())
+    }
+
+    /// 只订阅指定名称的手势事件
+    pub fn subscribe_gesture_named(&self, name: &str, cb: GestureCallback) -> SubscriptionId {
+        let name = name.to_owned();
+        self.subscribe_gesture(Box::new(move |event| {
+            if event.name == name {
+                cb(event);
+            }
+        }))
+    }
+
+    /// 注册额外的手势识别器，同名的识别器被替换
+    ///
+    /// 名称与内置摇动的 [`SHAKE_GESTURE`] 相同时不注册并返回 `false`。
+    pub fn register_recognizer(&self, name: &str, recognizer: Box<dyn GestureRecognizer>) -> bool {
+        self.inner
+            .state
+            .lock()
+            .unwrap()
+            .add_recognizer(name.to_owned(), recognizer)
+    }
+
+    /// 移除手势识别器，返回识别器是否存在
+    pub fn unregister_recognizer(&self, name: &str) -> bool {
+        self.inner.state.lock().unwrap().remove_recognizer(name)
+    }
+
+    /// 把实例恢复到刚创建时的状态，用于在测试用例之间复用同一个实例
+    ///
+    /// 清空全部订阅者和额外注册的手势识别器，丢弃进行中的拖拽状态、已读取的拖拽数据和丢弃计数。
+    /// 粘贴板、投递方式、摇动阈值和正在进行的监听与录制保持不变。
+    pub fn reset(&self) {
+        *self.subscriptions() = Subscriptions::default();
+        let change_count = self.inner.change_count();
+        {
+            let mut state = self.inner.state.lock().unwrap();
+            state.clear_recognizers();
+            state.reset(change_count);
+        }
+        *self.inner.data_cache.lock().unwrap() = None;
+        self.inner.dropped_events.store(0, Ordering::Relaxed);
+    }
+
+    // 恢复默认实例的设置，粘贴板恢复为在第一次使用时打开的平台默认实现
+    pub(crate) fn reset_settings(&self) {
+        *self.inner.pasteboard.write().unwrap() = None;
+        self.set_event_delivery(EventDelivery::default());
+        self.set_shake_config(ShakeConfig::DEFAULT);
+    }
+
+    /// 订阅拖拽结束，可以有多个订阅者
+    pub fn subscribe_drag_end(&self, cb: DragEndCallback) -> SubscriptionId {
+        self.subscriptions().drag_end.subscribe(cb.into())
+    }
+
+    /// 取消订阅，返回订阅是否存在
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscriptions = self.subscriptions();
+        subscriptions.pasteboard_changed.unsubscribe(id)
+            || subscriptions.mouse_shake.unsubscribe(id)
+            || subscriptions.shake_ended.unsubscribe(id)
+            || subscriptions.gesture.unsubscribe(id)
+            || subscriptions.drag_end.unsubscribe(id)
+    }
+
+    /// 获取拖拽事件流，可以在异步代码中逐个等待事件
+    #[cfg(feature = "async")]
+    pub fn drag_events(&self) -> crate::event_stream::DragEventStream {
+        crate::event_stream::DragEventStream::subscribe(self.clone())
+    }
+
+    /// 设置指针事件的投递方式，对之后启动的监听生效
+    pub fn set_event_delivery(&self, delivery: EventDelivery) {
+        *self.inner.delivery.lock().unwrap() = delivery;
+    }
+
+    /// 因事件队列已满而被丢弃的指针事件总数
+    pub fn dropped_event_count(&self) -> u64 {
+        self.inner.dropped_events.load(Ordering::Relaxed)
+    }
+
+    /// 获取当前的摇动检测阈值
+    pub fn shake_config(&self) -> ShakeConfig {
+        self.inner.state.lock().unwrap().shake_config()
+    }
+
+    /// 修改摇动检测阈值，立即对正在进行的拖拽生效
+    pub fn set_shake_config(&self, config: ShakeConfig) {
+        self.inner.state.lock().unwrap().set_shake_config(config);
+    }
+
+    /// 设置拖拽粘贴板，替换当前使用的实现
+    ///
+    /// 之后使用提供了配套粘贴板的后端开始监听时，会再被替换为后端的粘贴板。
+    pub fn set_drag_pasteboard(&self, pasteboard: Box<dyn DragPasteboard>) {
+        *self.inner.pasteboard.write().unwrap() = Some(Arc::from(pasteboard));
+        *self.inner.data_cache.lock().unwrap() = None;
+    }
+
+    /// 获取当前拖拽粘贴板内容，默认的粘贴板无法打开时返回 `None`
+    pub fn drag_pasteboard_data(&self) -> Option<DragData> {
+        let pasteboard = self.inner.pasteboard().ok()?;
+        let change_count = pasteboard.change_count();
+        self.inner.drag_data(&pasteboard, change_count)
+    }
+
+    /// 开始录制该实例处理的指针事件和读取到的拖拽数据，已有的录制会被直接替换
+    ///
+    /// 录制的轨迹可以交给 [`ReplayBackend`](crate::replay::ReplayBackend) 回放。
+    #[cfg(feature = "trace")]
+    pub fn start_recording(
+        &self,
+        writer: impl std::io::Write + Send + 'static,
+    ) -> std::io::Result<()> {
+        let recorder = crate::trace::TraceRecorder::new(writer, self.inner.clock.clone())?;
+        *self.inner.recorder.lock().unwrap() = Some(recorder);
+        // 让下一次读取重新读取拖拽数据，使轨迹中包含录制开始时的数据快照
+        *self.inner.data_cache.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// 停止录制并刷新缓冲，返回录制过程中遇到的第一个写入错误
+    #[cfg(feature = "trace")]
+    pub fn stop_recording(&self) -> std::io::Result<()> {
+        let recorder = self.inner.recorder.lock().unwrap().take();
+        recorder.map_or(Ok(()), crate::trace::TraceRecorder::finish)
+    }
+
+    /// 是否正在录制
+    #[cfg(feature = "trace")]
+    pub fn is_recording(&self) -> bool {
+        self.inner.recorder.lock().unwrap().is_some()
+    }
+
+    /// 使用指定的指针后端开始监听鼠标事件，返回的句柄被丢弃时停止监听
+    ///
+    /// 后端提供了配套的拖拽粘贴板（[`PointerBackend::drag_pasteboard`]）时改用它，
+    /// 否则在没有指定拖拽粘贴板时打开当前平台默认的实现，打开失败时返回错误。
+    pub fn start_listening_with<B: PointerBackend>(
+        &self,
+        mut backend: B,
+    ) -> Result<ListenerHandle, ListenerError> {
+        match backend.drag_pasteboard() {
+            Some(pasteboard) => self.set_drag_pasteboard(pasteboard),
+            None => {
+                self.inner.pasteboard().map_err(ListenerError::Backend)?;
+            }
+        }
+        let delivery = *self.inner.delivery.lock().unwrap();
+        match delivery {
+            EventDelivery::Synchronous => {
+                let inner = self.inner.clone();
+                backend.start_listening(Arc::new(move |event| {
+                    inner.handle_event(event, inner.change_count());
+                }))
+            }
+            EventDelivery::Queued { capacity, overflow } => {
+                let sampler = self.inner.clone();
+                let inner = self.inner.clone();
+                event_queue::start_queued(
+                    &mut backend,
+                    capacity,
+                    overflow,
+                    self.inner.dropped_events.clone(),
+                    Arc::new(move || sampler.change_count()),
+                    Arc::new(move |event, change_count| inner.handle_event(event, change_count)),
+                )
+            }
+        }
+    }
+
+    /// 使用当前平台默认的指针后端开始监听鼠标事件
+    ///
+    /// macOS 上事件监听安装在当前线程的运行循环上，可以调用 [`ListenerHandle::wait`] 运行它。
+    #[cfg(target_os = "macos")]
+    pub fn start_listening(&self) -> Result<ListenerHandle, ListenerError> {
+        self.start_listening_with(crate::macos::mouse_listener::MacPointerBackend)
+    }
+
+    /// 开始监听鼠标事件，连接 `DISPLAY` 指定的 X11 显示
+    #[cfg(all(target_os = "linux", feature = "x11"))]
+    pub fn start_listening(&self) -> Result<ListenerHandle, ListenerError> {
+        self.start_listening_with(crate::x11::pointer_listener::X11PointerBackend::new())
+    }
+
+    /// 在独立线程中使用指定的指针后端监听鼠标事件，后端初始化完成后返回
+    pub fn spawn_listener_with<B: PointerBackend + Send + 'static>(
+        &self,
+        backend: B,
+    ) -> Result<SpawnedListener, ListenerError> {
+        let dorazu = self.clone();
+        SpawnedListener::spawn(move || dorazu.start_listening_with(backend))
+    }
+
+    /// 在独立线程中开始监听鼠标事件，事件监听安装完成后返回
+    #[cfg(any(target_os = "macos", all(target_os = "linux", feature = "x11")))]
+    pub fn spawn_listener(&self) -> Result<SpawnedListener, ListenerError> {
+        let dorazu = self.clone();
+        SpawnedListener::spawn(move || dorazu.start_listening())
+    }
+
+    fn subscriptions(&self) -> std::sync::MutexGuard<'_, Subscriptions> {
+        self.inner.subscriptions.lock().unwrap()
+    }
+}
+
+// 处理指针事件时产生、需要通知订阅者的事件，拖拽数据在通知前才读取
+enum Notification {
+    PasteboardChanged,
+    Shake {
+        ordinal: usize,
+        axis: ShakeAxis,
+        info: ShakeInfo,
+    },
+    ShakeEnded {
+        ordinal: usize,
+    },
+    Gesture {
+        name: String,
+        detail: GestureDetail,
+    },
+    DragEnd,
+}
+
+impl Inner {
+    /// 处理一个指针事件，根据事件类型和位置推进拖拽状态
+    ///
+    /// `change_count` 是事件到达时（即后端调用回调时）读取的粘贴板变化计数，
+    /// 排队投递时事件被处理得晚，也按到达时的粘贴板状态判断拖拽是否开始。
+    /// 状态在持有锁时推进，拖拽数据的读取和订阅者的通知都在释放锁之后进行，
+    /// 回调中可以再次调用实例的方法。
+    fn handle_event(&self, event: PointerEvent, change_count: i64) {
+        let notifications = self.advance_state(event, change_count);
+        if notifications.is_empty() {
+            return;
+        }
+        let data = self
+            .pasteboard()
+            .ok()
+            .and_then(|pasteboard| self.drag_data(&pasteboard, change_count));
+        self.dispatch(notifications, data);
+    }
+
+    // 获取当前使用的拖拽粘贴板，尚未打开默认的实现时先打开它
+    fn pasteboard(&self) -> Result<Arc<dyn DragPasteboard>, Box<dyn Error + Send + Sync>> {
+        if let Some(pasteboard) = self.pasteboard.read().unwrap().as_ref() {
+            return Ok(pasteboard.clone());
+        }
+        let mut slot = self.pasteboard.write().unwrap();
+        match slot.as_ref() {
+            Some(pasteboard) => Ok(pasteboard.clone()),
+            None => Ok(slot.insert(pasteboard::default_drag_pasteboard()?).clone()),
+        }
+    }
+
+    // 读取拖拽粘贴板当前的变化计数，粘贴板尚未打开时为 0
+    fn change_count(&self) -> i64 {
+        let pasteboard = self.pasteboard.read().unwrap().clone();
+        pasteboard.map_or(0, |pasteboard| pasteboard.change_count())
+    }
+
+    // 读取粘贴板处于 `change_count` 时的拖拽数据，计数与上一次读取时相同时直接使用上一次的结果，
+    // 录制时同时以该计数写入数据快照，回放时按事件的计数取回同样的数据
+    fn drag_data(
+        &self,
+        pasteboard: &Arc<dyn DragPasteboard>,
+        change_count: i64,
+    ) -> Option<DragData> {
+        if let Some((cached_count, data)) = self.data_cache.lock().unwrap().as_ref()
+            && *cached_count == change_count
+        {
+            return data.clone();
+        }
+        let data = pasteboard.data_at(change_count);
+        #[cfg(feature = "trace")]
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            recorder.record_data(change_count, &data);
+        }
+        *self.data_cache.lock().unwrap() = Some((change_count, data.clone()));
+        data
+    }
+
+    // 推进拖拽状态，返回需要通知订阅者的事件
+    fn advance_state(&self, event: PointerEvent, change_count: i64) -> Vec<Notification> {
+        let mut state = self.state.lock().unwrap();
+        let mut notifications = Vec::new();
+
+        #[cfg(feature = "trace")]
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            recorder.record_pointer(&event, change_count);
+        }
+
+        match event {
+            PointerEvent::Down { button: MouseButton::Left, .. } => {
+                state.reset(change_count);
+            }
+            PointerEvent::Up { button: MouseButton::Left, .. } if state.has_dragging() => {
+                // 拖拽结束前先结束仍在持续的摇动
+                if let Some(ordinal) = state.end_active_shake() {
+                    notifications.push(Notification::ShakeEnded { ordinal });
+                }
+                notifications.extend(gesture_notifications(state.finish_gestures()));
+                notifications.push(Notification::DragEnd);
+                // 重置拖拽状态
+                state.reset(change_count);
+            }
+            PointerEvent::Dragged { button: MouseButton::Left, location, timestamp } => {
+                // 粘贴板变化会重置摇动检测，先结束仍在持续的摇动
+                if state.is_pasteboard_changed(change_count)
+                    && let Some(ordinal) = state.end_active_shake()
+                {
+                    notifications.push(Notification::ShakeEnded { ordinal });
+                }
+
+                // 检查拖拽时粘贴板是否有变化
+                if state.check_pasteboard_change(change_count) {
+                    // 标记拖拽已开始
+                    state.mark_dragging_started();
+                    notifications.push(Notification::PasteboardChanged);
+                }
+
+                // 粘贴板变化后把位置交给摇动检测和各个识别器
+                match state.add_position(location.x, location.y, timestamp) {
+                    Some(ShakeChange::Started { ordinal, axis, info }) => {
+                        notifications.push(Notification::Shake { ordinal, axis, info });
+                    }
+                    Some(ShakeChange::Ended { ordinal }) => {
+                        notifications.push(Notification::ShakeEnded { ordinal });
+                    }
+                    None => {}
+                }
+
+                // 上报额外注册的识别器识别到的手势
+                notifications.extend(gesture_notifications(state.take_gestures()));
+            }
+            _ => {}
+        }
+        notifications
+    }
+
+    // 按顺序通知订阅者，调用时不持有拖拽状态的锁
+    fn dispatch(&self, notifications: Vec<Notification>, data: Option<DragData>) {
+        for notification in notifications {
+            match notification {
+                Notification::PasteboardChanged => {
+                    let subscribers = self.subscriptions.lock().unwrap().pasteboard_changed.snapshot();
+                    notify(subscribers, data.clone());
+                }
+                Notification::Shake { ordinal, axis, info } => {
+                    let event = ShakeEvent {
+                        ordinal,
+                        axis,
+                        info,
+                        data: data.clone(),
+                    };
+                    let subscribers = self.subscriptions.lock().unwrap().mouse_shake.snapshot();
+                    notify(subscribers, event);
+                    // 内置的摇动同时作为手势事件上报
+                    let event = GestureEvent {
+                        name: SHAKE_GESTURE.to_owned(),
+                        detail: GestureDetail::Shake { ordinal, axis, info },
+                        data: data.clone(),
+                    };
+                    let subscribers = self.subscriptions.lock().unwrap().gesture.snapshot();
+                    notify(subscribers, event);
+                }
+                Notification::ShakeEnded { ordinal } => {
+                    let event = ShakeEndedEvent {
+                        ordinal,
+                        data: data.clone(),
+                    };
+                    let subscribers = self.subscriptions.lock().unwrap().shake_ended.snapshot();
+                    notify(subscribers, event);
+                }
+                Notification::Gesture { name, detail } => {
+                    let event = GestureEvent {
+                        name,
+                        detail,
+                        data: data.clone(),
+                    };
+                    let subscribers = self.subscriptions.lock().unwrap().gesture.snapshot();
+                    notify(subscribers, event);
+                }
+                Notification::DragEnd => {
+                    let subscribers = self.subscriptions.lock().unwrap().drag_end.snapshot();
+                    notify(subscribers, data.clone());
+                }
+            }
+        }
+    }
+}
+
+// 把识别到的手势转换为待通知的手势事件
+fn gesture_notifications(
+    gestures: Vec<(String, GestureDetail)>,
+) -> impl Iterator<Item = Notification> {
+    gestures
+        .into_iter()
+        .map(|(name, detail)| Notification::Gesture { name, detail })
+}
+
+// 把事件依次交给订阅者
+fn notify<E: Clone>(subscribers: Vec<Arc<dyn Fn(E) + Send + Sync>>, event: E) {
+    for subscriber in subscribers {
+        subscriber(event.clone());
+    }
+}
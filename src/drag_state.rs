@@ -1,21 +1,109 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::clock::Clock;
+use crate::gesture::{GestureRecognizer, SHAKE_GESTURE};
 use crate::shake_detector::ShakeDetector;
-use crate::macos::pasteboard_utils::get_drag_pasteboard_change_count;
+use crate::model::gesture_event::GestureDetail;
+use crate::model::pointer_event::Point;
+use crate::model::shake_event::{ShakeAxis, ShakeInfo};
+use crate::shake_config::ShakeConfig;
+
+// 内置摇动识别器在一个位置上的状态变化
+pub enum ShakeChange {
+    // 上报一次新的摇动
+    Started {
+        ordinal: usize,
+        axis: ShakeAxis,
+        info: ShakeInfo,
+    },
+    // 已上报的摇动停止
+    Ended { ordinal: usize },
+}
 
 pub struct DragState {
     initial_change_count: i64,
     pasteboard_changed: bool,
+    // 内置的摇动识别器，与额外注册的识别器一样通过 `GestureRecognizer` 驱动
     shake_detector: ShakeDetector,
+    // 额外注册的手势识别器及其名称
+    recognizers: Vec<(String, Box<dyn GestureRecognizer>)>,
+    // 已识别但尚未上报的手势
+    pending_gestures: Vec<(String, GestureDetail)>,
     dragging_started: bool,
+    // 事件不带时间戳时使用的时间来源
+    clock: Arc<dyn Clock>,
 }
 
 impl DragState {
-    pub fn new() -> Self {
+    pub fn new(shake_config: ShakeConfig, clock: Arc<dyn Clock>) -> Self {
         Self {
             initial_change_count: 0,
             pasteboard_changed: false,
-            shake_detector: ShakeDetector::new(),
+            shake_detector: ShakeDetector::new(shake_config),
+            recognizers: Vec::new(),
+            pending_gestures: Vec::new(),
             dragging_started: false,  // 新增初始化
+            clock,
+        }
+    }
+    
+    // 获取摇动检测阈值
+    pub fn shake_config(&self) -> ShakeConfig {
+        self.shake_detector.config()
+    }
+    
+    // 替换摇动检测阈值
+    pub fn set_shake_config(&mut self, config: ShakeConfig) {
+        self.shake_detector.set_config(config);
+    }
+    
+    // 注册手势识别器，同名的识别器被替换，与内置摇动同名时不注册并返回 false
+    pub fn add_recognizer(
+        &mut self,
+        name: String,
+        mut recognizer: Box<dyn GestureRecognizer>,
+    ) -> bool {
+        if name == SHAKE_GESTURE {
+            return false;
+        }
+        recognizer.reset();
+        match self.recognizers.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing)) => *existing = recognizer,
+            None => self.recognizers.push((name, recognizer)),
+        }
+        true
+    }
+    
+    // 移除手势识别器，返回是否存在
+    pub fn remove_recognizer(&mut self, name: &str) -> bool {
+        let len = self.recognizers.len();
+        self.recognizers.retain(|(existing, _)| existing != name);
+        self.recognizers.len() != len
+    }
+    
+    // 移除全部额外注册的手势识别器
+    pub fn clear_recognizers(&mut self) {
+        self.recognizers.clear();
+        self.pending_gestures.clear();
+    }
+    
+    // 取出已识别但尚未上报的手势
+    pub fn take_gestures(&mut self) -> Vec<(String, GestureDetail)> {
+        std::mem::take(&mut self.pending_gestures)
+    }
+    
+    // 拖拽结束，收集识别器基于完整路径给出的结果
+    pub fn finish_gestures(&mut self) -> Vec<(String, GestureDetail)> {
+        let mut gestures = self.take_gestures();
+        if self.pasteboard_changed {
+            for (name, recognizer) in &mut self.recognizers {
+                if let Some(detail) = recognizer.finish() {
+                    gestures.push((name.clone(), detail));
+                }
+            }
         }
+        gestures
     }
     
     // 新增拖拽开始标记方法
@@ -28,54 +116,84 @@ impl DragState {
         self.dragging_started
     }
     
-    // 修改重置方法
-    pub fn reset(&mut self) {
-        self.initial_change_count = get_drag_pasteboard_change_count();
+    // 修改重置方法，以事件到达时的粘贴板变化计数作为拖拽开始前的计数
+    pub fn reset(&mut self, change_count: i64) {
+        self.initial_change_count = change_count;
         self.pasteboard_changed = false;
         self.dragging_started = false;
-        self.shake_detector.reset();
+        self.reset_gestures();
+    }
+    
+    // 重置摇动检测和所有手势识别器
+    fn reset_gestures(&mut self) {
+        GestureRecognizer::reset(&mut self.shake_detector);
+        for (_, recognizer) in &mut self.recognizers {
+            recognizer.reset();
+        }
+        self.pending_gestures.clear();
+    }
+    
+    // 粘贴板变化后把鼠标位置交给内置的摇动识别器和额外注册的识别器
+    //
+    // 返回内置摇动识别器在该位置上的状态变化，其他识别器的结果留待 `take_gestures` 取出。
+    pub fn add_position(
+        &mut self,
+        x: f64,
+        y: f64,
+        timestamp: Option<Duration>,
+    ) -> Option<ShakeChange> {
+        if !self.pasteboard_changed {
+            return None;
+        }
+        // 事件不带时间戳时以时钟读数代替
+        let timestamp = timestamp.unwrap_or_else(|| self.clock.now());
+        let location = Point { x, y };
+        
+        let shake_was_active = self.shake_detector.is_shake_active();
+        let detail = GestureRecognizer::add_position(&mut self.shake_detector, location, timestamp);
+        let shake = match detail {
+            Some(GestureDetail::Shake { ordinal, axis, info }) => {
+                Some(ShakeChange::Started { ordinal, axis, info })
+            }
+            _ if shake_was_active && !self.shake_detector.is_shake_active() => {
+                Some(ShakeChange::Ended { ordinal: self.shake_detector.shake_count() })
+            }
+            _ => None,
+        };
+        
+        for (name, recognizer) in &mut self.recognizers {
+            if let Some(detail) = recognizer.add_position(location, timestamp) {
+                self.pending_gestures.push((name.clone(), detail));
+            }
+        }
+        shake
     }
     
-    // 添加鼠标位置并检测摇动
-    pub fn add_position(&mut self, x: f64, y: f64) {
-        // 添加鼠标位置并检测摇动
-        self.shake_detector.add_position(x, y);
+    // 判断事件到达时的粘贴板变化计数是否与拖拽开始时不同，不修改状态
+    pub fn is_pasteboard_changed(&self, current_count: i64) -> bool {
+        current_count != self.initial_change_count
     }
     
-    // 检查粘贴板变化
-    pub fn check_pasteboard_change(&mut self) -> bool {
-        let current_count = get_drag_pasteboard_change_count();
-        let pasteboard_changed = current_count != self.initial_change_count;
+    // 检查事件到达时粘贴板是否已经变化
+    pub fn check_pasteboard_change(&mut self, current_count: i64) -> bool {
+        let pasteboard_changed = self.is_pasteboard_changed(current_count);
         
         if pasteboard_changed {
-            // 标记粘贴板已变化，开始检测摇动
+            // 标记粘贴板已变化，开始识别手势
             self.pasteboard_changed = true;
             self.initial_change_count = current_count;
             
-            // 重置摇动检测状态，从粘贴板变化后开始检测
-            self.shake_detector.reset();
+            // 重置摇动检测和识别器，从粘贴板变化后开始识别
+            self.reset_gestures();
         }
         
         pasteboard_changed
     }
     
-    // 检查是否处于摇动状态
-    pub fn is_shaking(&self) -> bool {
-        // 只有粘贴板变化后才检测摇动
-        if !self.pasteboard_changed {
-            return false;
-        }
-        
-        self.shake_detector.is_shaking()
-    }
-    
-    // 检查是否已在当前拖拽中检测到摇动
-    pub fn is_shake_detected_in_current_drag(&self) -> bool {
-        self.shake_detector.is_shake_detected_in_current_drag()
+    // 结束仍在持续的摇动，返回结束的摇动的序号
+    pub fn end_active_shake(&mut self) -> Option<usize> {
+        self.shake_detector
+            .is_shake_active()
+            .then(|| self.shake_detector.end_shake())
     }
-    
-    // 设置已在当前拖拽中检测到摇动
-    pub fn set_shake_detected_in_current_drag(&mut self, value: bool) {
-        self.shake_detector.set_shake_detected_in_current_drag(value);
-    }
-}
\ No newline at end of file
+}
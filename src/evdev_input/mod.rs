@@ -0,0 +1 @@
+pub mod pointer_listener;
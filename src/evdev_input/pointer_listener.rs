@@ -0,0 +1,282 @@
+use std::error::Error;
+use std::io::{self, PipeReader, Write};
+use std::os::fd::AsRawFd;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::UNIX_EPOCH;
+
+use evdev::{
+    AbsoluteAxisCode, Device, EventSummary, KeyCode, RelativeAxisCode, SynchronizationCode,
+};
+
+use crate::backend::{
+    ListenerError, ListenerHandle, PointerBackend, PointerEventCallback, StopSignal, ThreadListener,
+};
+use crate::model::pointer_event::{MouseButton, Point, PointerEvent};
+
+// _IOW('E', 0xa0, int)，设置内核为事件记录时间所用的时钟
+const EVIOCSCLOCKID: libc::Ioctl = 0x400445a0;
+
+/// 直接读取 `/dev/input` 的 evdev 指针后端
+///
+/// 适用于 X11 和 Wayland 协议都无法提供全局指针数据的环境，需要对输入设备有读权限
+/// （通常是 `input` 用户组）。evdev 只提供相对位移，后端将其累加为从原点开始的虚拟坐标，
+/// 与屏幕坐标无关，但足以用于摇动检测。触控板的绝对坐标按前后两次采样的差值累加。
+#[derive(Debug, Default)]
+pub struct EvdevPointerBackend {
+    devices: Vec<PathBuf>,
+    state: Arc<Mutex<PointerState>>,
+}
+
+// 所有设备共享的指针状态
+#[derive(Debug, Default)]
+struct PointerState {
+    location: Point,
+    pressed: Option<MouseButton>,
+}
+
+impl EvdevPointerBackend {
+    /// 自动枚举所有带左键且能移动指针的设备
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 只监听指定的设备文件，例如 `/dev/input/event3`
+    pub fn with_devices(devices: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            devices: devices.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    // 打开待监听的设备
+    fn open_devices(&self) -> Result<Vec<Device>, Box<dyn Error + Send + Sync>> {
+        if !self.devices.is_empty() {
+            return self
+                .devices
+                .iter()
+                .map(|path| {
+                    Device::open(path).map_err(|err| format!("{}: {err}", path.display()).into())
+                })
+                .collect();
+        }
+
+        let devices: Vec<Device> = evdev::enumerate()
+            .map(|(_, device)| device)
+            .filter(is_pointer_device)
+            .collect();
+        if devices.is_empty() {
+            return Err("no readable pointer device in /dev/input, check permissions".into());
+        }
+        Ok(devices)
+    }
+}
+
+impl PointerBackend for EvdevPointerBackend {
+    fn start_listening(
+        &mut self,
+        callback: PointerEventCallback,
+    ) -> Result<ListenerHandle, ListenerError> {
+        let devices = self.open_devices()?;
+        let (wake_reader, wake_writer) =
+            io::pipe().map_err(|err| ListenerError::Backend(err.into()))?;
+        let wake_reader = Arc::new(wake_reader);
+        let signal = Arc::new(StopSignal::default());
+
+        // 每个设备一个读取线程，全部设备断开后监听结束
+        let workers: Vec<_> = devices
+            .into_iter()
+            .map(|device| {
+                let callback = callback.clone();
+                let state = self.state.clone();
+                let signal = signal.clone();
+                let wake_reader = wake_reader.clone();
+                thread::spawn(move || read_device(device, callback, state, &signal, &wake_reader))
+            })
+            .collect();
+        {
+            let signal = signal.clone();
+            thread::spawn(move || {
+                for worker in workers {
+                    let _ = worker.join();
+                }
+                signal.stop();
+            });
+        }
+
+        Ok(ListenerHandle::new(ThreadListener::new(
+            signal,
+            move || {
+                // 唤醒管道中写入的数据不会被读走，所有读取线程的 poll 都会返回
+                let _ = (&wake_writer).write_all(&[0]);
+            },
+        )))
+    }
+
+    fn current_location(&self) -> Point {
+        self.state.lock().unwrap().location
+    }
+}
+
+// 等待设备可读或被唤醒，出错时返回 false
+fn wait_readable(device: &Device, wake_reader: &PipeReader) -> bool {
+    let mut fds = [
+        libc::pollfd {
+            fd: device.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: wake_reader.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    loop {
+        let result = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) };
+        if result >= 0 {
+            return fds[0].revents & (libc::POLLERR | libc::POLLHUP | libc::POLLNVAL) == 0;
+        }
+        if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return false;
+        }
+    }
+}
+
+// 让内核以 CLOCK_MONOTONIC 记录事件时间，默认的 CLOCK_REALTIME 会随系统时间调整而跳变
+fn use_monotonic_clock(device: &Device) -> bool {
+    let clock_id: libc::c_int = libc::CLOCK_MONOTONIC;
+    unsafe { libc::ioctl(device.as_raw_fd(), EVIOCSCLOCKID, &clock_id) == 0 }
+}
+
+// 判断设备是否为带左键的鼠标或触控板
+fn is_pointer_device(device: &Device) -> bool {
+    let has_left_button = device
+        .supported_keys()
+        .is_some_and(|keys| keys.contains(KeyCode::BTN_LEFT));
+    let has_relative_motion = device.supported_relative_axes().is_some_and(|axes| {
+        axes.contains(RelativeAxisCode::REL_X) && axes.contains(RelativeAxisCode::REL_Y)
+    });
+    let has_absolute_motion = device.supported_absolute_axes().is_some_and(|axes| {
+        axes.contains(AbsoluteAxisCode::ABS_X) && axes.contains(AbsoluteAxisCode::ABS_Y)
+    });
+    has_left_button && (has_relative_motion || has_absolute_motion)
+}
+
+// 将 evdev 按键转换为鼠标按键
+fn to_mouse_button(code: KeyCode) -> Option<MouseButton> {
+    match code {
+        KeyCode::BTN_LEFT => Some(MouseButton::Left),
+        KeyCode::BTN_RIGHT => Some(MouseButton::Right),
+        KeyCode::BTN_MIDDLE => Some(MouseButton::Other(2)),
+        _ => None,
+    }
+}
+
+// 读取单个设备的事件，按 SYN_REPORT 分组累加位移
+fn read_device(
+    mut device: Device,
+    callback: PointerEventCallback,
+    state: Arc<Mutex<PointerState>>,
+    signal: &StopSignal,
+    wake_reader: &PipeReader,
+) {
+    // 本组事件累计的位移
+    let (mut dx, mut dy) = (0.0, 0.0);
+    // 触控板上一次的绝对坐标，手指抬起后清空
+    let (mut last_abs_x, mut last_abs_y): (Option<i32>, Option<i32>) = (None, None);
+    // 无法切换到单调时钟时不使用内核时间，由状态机的时钟代替
+    let monotonic = use_monotonic_clock(&device);
+
+    loop {
+        if !wait_readable(&device, wake_reader) || signal.is_stopped() {
+            return;
+        }
+        let events = match device.fetch_events() {
+            Ok(events) => events.collect::<Vec<_>>(),
+            // 设备断开或不可读时结束该设备的监听
+            Err(_) => return,
+        };
+
+        for event in events {
+            // 内核为每个事件记录的单调时钟时间，evdev 按系统时间的格式给出
+            let timestamp = monotonic
+                .then(|| event.timestamp().duration_since(UNIX_EPOCH).ok())
+                .flatten();
+            let pointer_event = match event.destructure() {
+                EventSummary::RelativeAxis(_, RelativeAxisCode::REL_X, value) => {
+                    dx += value as f64;
+                    None
+                }
+                EventSummary::RelativeAxis(_, RelativeAxisCode::REL_Y, value) => {
+                    dy += value as f64;
+                    None
+                }
+                EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_X, value) => {
+                    if let Some(last) = last_abs_x.replace(value) {
+                        dx += (value - last) as f64;
+                    }
+                    None
+                }
+                EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_Y, value) => {
+                    if let Some(last) = last_abs_y.replace(value) {
+                        dy += (value - last) as f64;
+                    }
+                    None
+                }
+                EventSummary::Key(_, KeyCode::BTN_TOUCH, 0) => {
+                    last_abs_x = None;
+                    last_abs_y = None;
+                    None
+                }
+                EventSummary::Key(_, code, value) => to_mouse_button(code).and_then(|button| {
+                    let mut state = state.lock().unwrap();
+                    let location = state.location;
+                    match value {
+                        1 => {
+                            state.pressed.get_or_insert(button);
+                            Some(PointerEvent::Down {
+                                button,
+                                location,
+                                timestamp,
+                            })
+                        }
+                        0 => {
+                            if state.pressed == Some(button) {
+                                state.pressed = None;
+                            }
+                            Some(PointerEvent::Up {
+                                button,
+                                location,
+                                timestamp,
+                            })
+                        }
+                        // 按键自动重复与鼠标无关
+                        _ => None,
+                    }
+                }),
+                EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _)
+                    if dx != 0.0 || dy != 0.0 =>
+                {
+                    let mut state = state.lock().unwrap();
+                    state.location.x += dx;
+                    state.location.y += dy;
+                    (dx, dy) = (0.0, 0.0);
+                    let location = state.location;
+                    state.pressed.map(|button| PointerEvent::Dragged {
+                        button,
+                        location,
+                        timestamp,
+                    })
+                }
+                _ => None,
+            };
+
+            // 上报时不持有共享状态的锁
+            if let Some(pointer_event) = pointer_event {
+                callback(pointer_event);
+            }
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::backend::{
+    ListenerControl, ListenerError, ListenerHandle, PointerBackend, PointerEventCallback,
+};
+use crate::model::pointer_event::PointerEvent;
+
+/// 默认的事件队列容量
+pub const DEFAULT_QUEUE_CAPACITY: usize = 256;
+
+/// 指针事件交给状态机处理的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventDelivery {
+    /// 在后端回调中直接处理，订阅者的回调也运行在后端线程上
+    Synchronous,
+    /// 后端回调只把事件和此时的粘贴板变化计数放入有界队列，由独立的处理线程运行状态机和订阅者回调
+    ///
+    /// 订阅者的回调发生 panic 时，处理线程放弃当前事件并继续处理之后的事件。
+    Queued {
+        capacity: usize,
+        overflow: OverflowPolicy,
+    },
+}
+
+impl Default for EventDelivery {
+    fn default() -> Self {
+        EventDelivery::Queued {
+            capacity: DEFAULT_QUEUE_CAPACITY,
+            overflow: OverflowPolicy::CoalesceMoves,
+        }
+    }
+}
+
+/// 队列已满时的处理策略，被丢弃的事件计入 [`Dorazu::dropped_event_count`](crate::Dorazu::dropped_event_count)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// 丢弃队列中最早的事件
+    DropOldest,
+    /// 丢弃新到达的事件
+    DropNewest,
+    /// 合并拖动事件：新的拖动事件替换队尾同一按键的拖动事件，否则挤掉队列中最早的拖动事件；
+    /// 队列中没有拖动事件时丢弃新的拖动事件，按下和抬起事件此时挤掉其中最早的一个
+    CoalesceMoves,
+}
+
+// 有界事件队列
+struct EventQueue {
+    state: Mutex<QueueState>,
+    condvar: Condvar,
+    capacity: usize,
+    overflow: OverflowPolicy,
+    // 被丢弃的事件数，由所属实例共享
+    dropped: Arc<AtomicU64>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    events: VecDeque<QueuedEvent>,
+    closed: bool,
+}
+
+// 排队中的事件及其到达时的粘贴板变化计数
+struct QueuedEvent {
+    event: PointerEvent,
+    change_count: i64,
+}
+
+impl EventQueue {
+    fn new(capacity: usize, overflow: OverflowPolicy, dropped: Arc<AtomicU64>) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            condvar: Condvar::new(),
+            capacity: capacity.max(1),
+            overflow,
+            dropped,
+        }
+    }
+
+    // 放入事件，不会阻塞调用方
+    fn push(&self, queued: QueuedEvent) {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return;
+        }
+        let events = &mut state.events;
+        if events.len() >= self.capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            match self.overflow {
+                OverflowPolicy::DropOldest => {
+                    events.pop_front();
+                }
+                OverflowPolicy::DropNewest => return,
+                OverflowPolicy::CoalesceMoves => {
+                    if let PointerEvent::Dragged { button, .. } = queued.event
+                        && let Some(last) = events.back_mut()
+                        && matches!(last.event, PointerEvent::Dragged { .. })
+                        && last.event.button() == button
+                    {
+                        *last = queued;
+                        self.condvar.notify_one();
+                        return;
+                    }
+                    let oldest_move = events
+                        .iter()
+                        .position(|queued| matches!(queued.event, PointerEvent::Dragged { .. }));
+                    match oldest_move {
+                        Some(index) => {
+                            events.remove(index);
+                        }
+                        // 没有可以合并的拖动事件时，优先丢弃新到达的拖动事件
+                        None if matches!(queued.event, PointerEvent::Dragged { .. }) => return,
+                        None => {
+                            events.pop_front();
+                        }
+                    }
+                }
+            }
+        }
+        events.push_back(queued);
+        self.condvar.notify_one();
+    }
+
+    // 取出下一个事件，队列关闭且为空时返回 `None`
+    fn pop(&self) -> Option<QueuedEvent> {
+        let state = self.state.lock().unwrap();
+        let mut state = self
+            .condvar
+            .wait_while(state, |state| state.events.is_empty() && !state.closed)
+            .unwrap();
+        state.events.pop_front()
+    }
+
+    // 关闭队列，已排队的事件仍会被取出
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.condvar.notify_all();
+    }
+}
+
+// 后端监听器加上处理线程，结束时处理完队列中剩余的事件
+struct QueuedListener {
+    inner: ListenerHandle,
+    queue: Arc<EventQueue>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl QueuedListener {
+    // 关闭队列并等待处理线程退出，在处理线程自身中调用时不等待
+    fn finish(&self) {
+        self.queue.close();
+        let mut worker = self.worker.lock().unwrap();
+        if worker
+            .as_ref()
+            .is_some_and(|worker| worker.thread().id() != thread::current().id())
+            && let Some(worker) = worker.take()
+        {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl ListenerControl for QueuedListener {
+    fn stop(&self) {
+        self.inner.stop();
+        self.finish();
+    }
+
+    fn wait(&self) {
+        self.inner.wait();
+        self.finish();
+    }
+}
+
+/// 启动后端，事件经过有界队列交给处理线程中的 `callback`
+///
+/// 事件入队时在后端回调中调用 `change_count` 读取粘贴板变化计数，随事件一起交给 `callback`。
+pub(crate) fn start_queued<B: PointerBackend>(
+    backend: &mut B,
+    capacity: usize,
+    overflow: OverflowPolicy,
+    dropped: Arc<AtomicU64>,
+    change_count: Arc<dyn Fn() -> i64 + Send + Sync>,
+    callback: Arc<dyn Fn(PointerEvent, i64) + Send + Sync>,
+) -> Result<ListenerHandle, ListenerError> {
+    let queue = Arc::new(EventQueue::new(capacity, overflow, dropped));
+    let worker = {
+        let queue = queue.clone();
+        thread::Builder::new()
+            .name("dorazu-events".to_owned())
+            .spawn(move || {
+                while let Some(queued) = queue.pop() {
+                    // 订阅者的回调发生 panic 时只放弃这一个事件，处理线程继续取出之后的事件
+                    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                        callback(queued.event, queued.change_count)
+                    }));
+                }
+            })
+            .map_err(|err| ListenerError::Backend(err.into()))?
+    };
+
+    let enqueue: PointerEventCallback = {
+        let queue = queue.clone();
+        Arc::new(move |event| {
+            queue.push(QueuedEvent {
+                event,
+                change_count: change_count(),
+            })
+        })
+    };
+    match backend.start_listening(enqueue) {
+        Ok(inner) => Ok(ListenerHandle::new(QueuedListener {
+            inner,
+            queue,
+            worker: Mutex::new(Some(worker)),
+        })),
+        Err(err) => {
+            queue.close();
+            let _ = worker.join();
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+    use crate::Dorazu;
+    use crate::mock::{MockPasteboard, MockPointerBackend};
+    use crate::model::pointer_event::{MouseButton, Point};
+
+    // 以变化计数标记事件，便于检查哪些事件留在队列中
+    fn queued(
+        event: fn(MouseButton, Point) -> PointerEvent,
+        button: MouseButton,
+        id: i64,
+    ) -> QueuedEvent {
+        QueuedEvent {
+            event: event(
+                button,
+                Point {
+                    x: id as f64,
+                    y: 0.0,
+                },
+            ),
+            change_count: id,
+        }
+    }
+
+    fn down(button: MouseButton, location: Point) -> PointerEvent {
+        PointerEvent::Down {
+            button,
+            location,
+            timestamp: None,
+        }
+    }
+
+    fn dragged(button: MouseButton, location: Point) -> PointerEvent {
+        PointerEvent::Dragged {
+            button,
+            location,
+            timestamp: None,
+        }
+    }
+
+    fn up(button: MouseButton, location: Point) -> PointerEvent {
+        PointerEvent::Up {
+            button,
+            location,
+            timestamp: None,
+        }
+    }
+
+    // 关闭队列并按顺序取出剩余事件的标记
+    fn drain(queue: &EventQueue) -> Vec<i64> {
+        queue.close();
+        std::iter::from_fn(|| queue.pop())
+            .map(|queued| queued.change_count)
+            .collect()
+    }
+
+    fn queue(capacity: usize, overflow: OverflowPolicy) -> (EventQueue, Arc<AtomicU64>) {
+        let dropped = Arc::new(AtomicU64::new(0));
+        (
+            EventQueue::new(capacity, overflow, dropped.clone()),
+            dropped,
+        )
+    }
+
+    #[test]
+    fn drop_oldest_discards_front() {
+        let (queue, dropped) = queue(2, OverflowPolicy::DropOldest);
+        queue.push(queued(down, MouseButton::Left, 1));
+        queue.push(queued(dragged, MouseButton::Left, 2));
+        queue.push(queued(up, MouseButton::Left, 3));
+
+        assert_eq!(drain(&queue), [2, 3]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn drop_newest_discards_incoming() {
+        let (queue, dropped) = queue(2, OverflowPolicy::DropNewest);
+        queue.push(queued(down, MouseButton::Left, 1));
+        queue.push(queued(dragged, MouseButton::Left, 2));
+        queue.push(queued(up, MouseButton::Left, 3));
+
+        assert_eq!(drain(&queue), [1, 2]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn coalesce_replaces_trailing_move_of_same_button() {
+        let (queue, dropped) = queue(2, OverflowPolicy::CoalesceMoves);
+        queue.push(queued(down, MouseButton::Left, 1));
+        queue.push(queued(dragged, MouseButton::Left, 2));
+        queue.push(queued(dragged, MouseButton::Left, 3));
+
+        assert_eq!(drain(&queue), [1, 3]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn coalesce_evicts_oldest_move_for_other_events() {
+        let (queue, dropped) = queue(3, OverflowPolicy::CoalesceMoves);
+        queue.push(queued(down, MouseButton::Left, 1));
+        queue.push(queued(dragged, MouseButton::Left, 2));
+        queue.push(queued(dragged, MouseButton::Right, 3));
+        // 按键不同的拖动事件不合并，挤掉最早的拖动事件
+        queue.push(queued(dragged, MouseButton::Left, 4));
+        // 抬起事件同样挤掉最早的拖动事件，按下事件保留
+        queue.push(queued(up, MouseButton::Left, 5));
+
+        assert_eq!(drain(&queue), [1, 4, 5]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn coalesce_without_moves_keeps_presses_and_releases() {
+        let (queue, dropped) = queue(2, OverflowPolicy::CoalesceMoves);
+        queue.push(queued(down, MouseButton::Left, 1));
+        queue.push(queued(up, MouseButton::Left, 2));
+        // 没有可以合并的拖动事件时丢弃新的拖动事件
+        queue.push(queued(dragged, MouseButton::Left, 3));
+        // 按下事件挤掉最早的事件
+        queue.push(queued(down, MouseButton::Left, 4));
+
+        assert_eq!(drain(&queue), [2, 4]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn panicking_subscriber_does_not_stop_delivery() {
+        let pasteboard = MockPasteboard::new();
+        let dorazu = Dorazu::builder()
+            .pasteboard(Box::new(pasteboard.clone()))
+            .event_delivery(EventDelivery::default())
+            .build();
+        let panicked = Arc::new(AtomicBool::new(false));
+        {
+            let panicked = panicked.clone();
+            dorazu.subscribe_pasteboard_changed(Box::new(move |_| {
+                if !panicked.swap(true, Ordering::SeqCst) {
+                    panic!("subscriber failure");
+                }
+            }));
+        }
+        let drag_ends = Arc::new(AtomicU64::new(0));
+        {
+            let drag_ends = drag_ends.clone();
+            dorazu.subscribe_drag_end(Box::new(move |_| {
+                drag_ends.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        let backend = MockPointerBackend::new();
+        let listener = dorazu.start_listening_with(backend.clone()).unwrap();
+        for _ in 0..3 {
+            backend.press(0.0, 0.0);
+            pasteboard.clear();
+            backend.drag_to(10.0, 0.0);
+            backend.release(10.0, 0.0);
+        }
+        // 停止监听时处理完队列中剩余的事件
+        listener.stop();
+
+        assert!(panicked.load(Ordering::SeqCst));
+        assert_eq!(drag_ends.load(Ordering::SeqCst), 3);
+        assert_eq!(dorazu.dropped_event_count(), 0);
+    }
+
+    #[test]
+    fn closed_queue_ignores_new_events() {
+        let (queue, dropped) = queue(2, OverflowPolicy::DropOldest);
+        queue.push(queued(down, MouseButton::Left, 1));
+        queue.close();
+        queue.push(queued(up, MouseButton::Left, 2));
+
+        assert_eq!(drain(&queue), [1]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 0);
+    }
+}
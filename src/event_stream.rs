@@ -0,0 +1,129 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use futures_core::Stream;
+
+use crate::Dorazu;
+use crate::gesture::SHAKE_GESTURE;
+use crate::model::drag_event::DragEvent;
+use crate::model::gesture_event::GestureEvent;
+use crate::subscription::SubscriptionId;
+
+/// 拖拽事件流，由 [`Dorazu::drag_events`] 创建，被丢弃时自动取消订阅
+pub struct DragEventStream {
+    receiver: UnboundedReceiver<DragEvent>,
+    dorazu: Dorazu,
+    subscriptions: Vec<SubscriptionId>,
+}
+
+impl DragEventStream {
+    // 订阅全部拖拽事件并转发到流中
+    pub(crate) fn subscribe(dorazu: Dorazu) -> Self {
+        let (sender, receiver) = unbounded();
+        let subscriptions = vec![
+            dorazu.subscribe_pasteboard_changed(forward(&sender, DragEvent::PasteboardChanged)),
+            dorazu.subscribe_shake(forward(&sender, DragEvent::Shake)),
+            dorazu.subscribe_shake_ended(forward(&sender, DragEvent::ShakeEnded)),
+            dorazu.subscribe_gesture(forward_gestures(&sender)),
+            dorazu.subscribe_drag_end(forward(&sender, DragEvent::DragEnd)),
+        ];
+        Self {
+            receiver,
+            dorazu,
+            subscriptions,
+        }
+    }
+}
+
+// 创建把回调参数包装成拖拽事件发送到流中的订阅者
+fn forward<T: 'static>(
+    sender: &UnboundedSender<DragEvent>,
+    make_event: fn(T) -> DragEvent,
+) -> Box<dyn Fn(T) + Send + Sync + 'static> {
+    let sender = sender.clone();
+    Box::new(move |value| {
+        // 接收端已经丢弃时忽略
+        let _ = sender.unbounded_send(make_event(value));
+    })
+}
+
+// 转发手势事件，内置的摇动已经作为 `DragEvent::Shake` 转发，不再重复
+fn forward_gestures(
+    sender: &UnboundedSender<DragEvent>,
+) -> Box<dyn Fn(GestureEvent) + Send + Sync + 'static> {
+    let forward = forward(sender, DragEvent::Gesture);
+    Box::new(move |event| {
+        if event.name != SHAKE_GESTURE {
+            forward(event);
+        }
+    })
+}
+
+impl Stream for DragEventStream {
+    type Item = DragEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<DragEvent>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for DragEventStream {
+    fn drop(&mut self) {
+        for id in self.subscriptions.drain(..) {
+            self.dorazu.unsubscribe(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_queue::EventDelivery;
+    use crate::gesture::{CircleConfig, CircleDetector};
+    use crate::mock::{MockPasteboard, MockPointerBackend};
+    use crate::model::pointer_event::{MouseButton, Point, PointerEvent};
+    use std::time::Duration;
+
+    #[test]
+    fn built_in_shake_is_streamed_once() {
+        let pasteboard = MockPasteboard::new();
+        let dorazu = Dorazu::builder()
+            .pasteboard(Box::new(pasteboard.clone()))
+            .event_delivery(EventDelivery::Synchronous)
+            .build();
+        // 内置摇动的名称不能用于注册其他识别器
+        let circle = || Box::new(CircleDetector::new(CircleConfig::default()));
+        assert!(!dorazu.register_recognizer(SHAKE_GESTURE, circle()));
+        assert!(dorazu.register_recognizer("circle", circle()));
+
+        let mut stream = dorazu.drag_events();
+        let backend = MockPointerBackend::new();
+        let _listener = dorazu.start_listening_with(backend.clone()).unwrap();
+        backend.press(0.0, 0.0);
+        pasteboard.clear();
+        for index in 0..10u64 {
+            backend.emit(PointerEvent::Dragged {
+                button: MouseButton::Left,
+                location: Point {
+                    x: if index % 2 == 0 { 50.0 } else { 0.0 },
+                    y: 0.0,
+                },
+                timestamp: Some(Duration::from_millis(index * 20)),
+            });
+        }
+        backend.release(0.0, 0.0);
+
+        let mut kinds = Vec::new();
+        while let Ok(event) = stream.receiver.try_recv() {
+            kinds.push(match event {
+                DragEvent::PasteboardChanged(_) => "changed".to_owned(),
+                DragEvent::Shake(event) => format!("shake {}", event.ordinal),
+                DragEvent::ShakeEnded(event) => format!("shake ended {}", event.ordinal),
+                DragEvent::Gesture(event) => format!("gesture {}", event.name),
+                DragEvent::DragEnd(_) => "end".to_owned(),
+            });
+        }
+        assert_eq!(kinds, ["changed", "shake 1", "shake ended 1", "end"]);
+    }
+}
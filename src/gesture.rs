@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use crate::model::gesture_event::GestureDetail;
+use crate::model::pointer_event::Point;
+
+pub use crate::circle_detector::{CircleConfig, CircleDetector};
+pub use crate::shake_detector::ShakeDetector;
+pub use crate::template_recognizer::{GestureTemplate, TemplateConfig, TemplateRecognizer};
+
+/// 内置摇动识别器上报手势事件时使用的名称
+pub const SHAKE_GESTURE: &str = "shake";
+
+/// 手势识别器，按顺序接收拖拽过程中的指针位置
+///
+/// 识别器只在拖拽粘贴板变化之后接收位置，每次开始新的拖拽或粘贴板再次变化时先被重置。
+/// 注册时指定的名称会作为手势事件的名称。
+pub trait GestureRecognizer: Send {
+    /// 清空状态，开始识别新的一次拖拽
+    fn reset(&mut self);
+
+    /// 输入一个指针位置和它的时间戳，识别到手势时返回识别结果
+    fn add_position(&mut self, location: Point, timestamp: Duration) -> Option<GestureDetail>;
+
+    /// 拖拽结束时调用，需要完整路径才能判断的识别器可以在此返回结果
+    fn finish(&mut self) -> Option<GestureDetail> {
+        None
+    }
+}
+
+impl GestureRecognizer for ShakeDetector {
+    fn reset(&mut self) {
+        ShakeDetector::reset(self);
+    }
+
+    fn add_position(&mut self, location: Point, timestamp: Duration) -> Option<GestureDetail> {
+        ShakeDetector::add_position(self, location.x, location.y, timestamp);
+        if self.has_shake_ended() {
+            self.end_shake();
+        }
+        if !self.should_report_shake() {
+            return None;
+        }
+        Some(GestureDetail::Shake {
+            ordinal: self.begin_shake(),
+            axis: self.shake_axis(),
+            info: self.shake_info(),
+        })
+    }
+}
@@ -1,115 +1,228 @@
-use std::sync::Mutex;
-use core_graphics::event::CGEventType;
-use core_graphics::geometry::CGPoint;
+use gesture::GestureRecognizer;
 use model::drag_types::DragData;
+use model::gesture_event::GestureEvent;
+use model::shake_event::{ShakeAxis, ShakeEndedEvent, ShakeEvent};
 
 mod shake_detector;
 mod drag_state;
+#[cfg(target_os = "macos")]
 mod macos;
+pub mod backend;
+mod circle_detector;
+pub mod clock;
+mod dorazu;
+#[cfg(all(target_os = "linux", feature = "evdev"))]
+pub mod evdev_input;
+pub mod event_queue;
+pub mod gesture;
+#[cfg(feature = "async")]
+pub mod event_stream;
+#[cfg(all(target_os = "linux", any(feature = "x11", feature = "wayland")))]
+mod mime_data;
+#[cfg(any(test, feature = "testing"))]
+pub mod mock;
 pub mod model;
-
-use crate::macos::mouse_listener;
-use drag_state::DragState;
+pub mod pasteboard;
+#[cfg(feature = "trace")]
+pub mod replay;
+pub mod shake_config;
+pub mod subscription;
+mod template_recognizer;
+#[cfg(feature = "trace")]
+pub mod trace;
+#[cfg(all(target_os = "linux", feature = "wayland"))]
+pub mod wayland;
+#[cfg(all(target_os = "linux", feature = "x11"))]
+pub mod x11;
+
+use backend::{ListenerError, ListenerHandle, PointerBackend, SpawnedListener};
+use event_queue::EventDelivery;
+use pasteboard::DragPasteboard;
+use shake_config::ShakeConfig;
 use std::sync::LazyLock;
+use subscription::SubscriptionId;
+
+pub use dorazu::{Dorazu, DorazuBuilder};
 
 /// 当拖拽粘贴板内容变化时的回调类型，参数为粘贴板类型列表
 pub type PasteboardChangedCallback = Box<dyn Fn(Option<DragData>) + Send + Sync + 'static>;
 /// 当检测到鼠标摇动时的回调类型
 pub type MouseShakeCallback = Box<dyn Fn(Option<DragData>) + Send + Sync + 'static>;
 
-/// 内部状态，保存拖拽过程中的数据
-static STATE: LazyLock<Mutex<DragState>> = LazyLock::new(|| Mutex::new(DragState::new()));
-/// 拖拽粘贴板变化回调
-static PASTEBOARD_CALLBACK: LazyLock<Mutex<Option<PasteboardChangedCallback>>> =
-    LazyLock::new(|| Mutex::new(None));
-/// 鼠标摇动回调
-static SHAKE_CALLBACK: LazyLock<Mutex<Option<MouseShakeCallback>>> =
-    LazyLock::new(|| Mutex::new(None));
+/// 当检测到鼠标摇动时的回调类型，参数包含摇动方向
+pub type ShakeCallback = Box<dyn Fn(ShakeEvent) + Send + Sync + 'static>;
+
+/// 当已上报的摇动停止时的回调类型
+pub type ShakeEndedCallback = Box<dyn Fn(ShakeEndedEvent) + Send + Sync + 'static>;
+
+/// 当识别到手势时的回调类型
+pub type GestureCallback = Box<dyn Fn(GestureEvent) + Send + Sync + 'static>;
+
+/// 当拖拽结束时的回调类型
+pub type DragEndCallback = Box<dyn Fn(Option<DragData>) + Send + Sync + 'static>;
+
+/// 自由函数使用的默认实例
+static DEFAULT: LazyLock<Dorazu> = LazyLock::new(|| Dorazu::builder().build());
+
+/// 获取自由函数使用的默认实例
+pub fn default_instance() -> &'static Dorazu {
+    &DEFAULT
+}
 
-/// 设置拖拽粘贴板内容变化的回调函数
+/// 把默认实例恢复到初始状态，用于在测试用例之间隔离自由函数的调用
+///
+/// 除 [`Dorazu::reset`] 清除的状态外，还恢复默认的粘贴板、投递方式和摇动阈值。
+/// 正在进行的监听和录制不受影响。
+pub fn reset() {
+    DEFAULT.reset_settings();
+    DEFAULT.reset();
+}
+
+/// 设置拖拽粘贴板内容变化的回调函数，替换上一次设置的回调，不影响其他订阅
 pub fn set_pasteboard_changed_callback(cb: PasteboardChangedCallback) {
-    let mut callback = PASTEBOARD_CALLBACK.lock().unwrap();
-    *callback = Some(cb);
+    DEFAULT.set_pasteboard_changed_callback(cb);
 }
 
-/// 设置鼠标摇动检测的回调函数
+/// 设置鼠标摇动检测的回调函数，替换上一次设置的回调，不影响其他订阅
 pub fn set_mouse_shake_callback(cb: MouseShakeCallback) {
-    let mut callback = SHAKE_CALLBACK.lock().unwrap();
-    *callback = Some(cb);
+    DEFAULT.set_mouse_shake_callback(cb);
 }
 
-/// 设置拖拽结束回调函数
+/// 设置拖拽结束回调函数，替换上一次设置的回调，不影响其他订阅
 pub fn set_drag_end_callback(cb: DragEndCallback) {
-    let mut callback = DRAG_END_CALLBACK.lock().unwrap();
-    *callback = Some(cb);
+    DEFAULT.set_drag_end_callback(cb);
 }
 
-/// 内部回调，由鼠标监听模块调用，根据事件类型和位置进行处理
-/// 当拖拽结束时的回调类型
-pub type DragEndCallback = Box<dyn Fn(Option<DragData>) + Send + Sync + 'static>;
+/// 订阅拖拽粘贴板内容变化，可以有多个订阅者
+pub fn subscribe_pasteboard_changed(cb: PasteboardChangedCallback) -> SubscriptionId {
+    DEFAULT.subscribe_pasteboard_changed(cb)
+}
+
+/// 订阅鼠标摇动，可以有多个订阅者
+pub fn subscribe_mouse_shake(cb: MouseShakeCallback) -> SubscriptionId {
+    DEFAULT.subscribe_mouse_shake(cb)
+}
+
+/// 订阅鼠标摇动，回调可以得到摇动方向等信息
+pub fn subscribe_shake(cb: ShakeCallback) -> SubscriptionId {
+    DEFAULT.subscribe_shake(cb)
+}
+
+/// 只订阅指定方向的鼠标摇动
+pub fn subscribe_shake_axis(axis: ShakeAxis, cb: ShakeCallback) -> SubscriptionId {
+    DEFAULT.subscribe_shake_axis(axis, cb)
+}
+
+/// 订阅摇动停止，在下一次指针事件或拖拽结束时发现摇动已经停止后上报
+pub fn subscribe_shake_ended(cb: ShakeEndedCallback) -> SubscriptionId {
+    DEFAULT.subscribe_shake_ended(cb)
+}
+
+/// 订阅全部手势事件，包括内置的摇动和额外注册的识别器
+pub fn subscribe_gesture(cb: GestureCallback) -> SubscriptionId {
+    DEFAULT.subscribe_gesture(cb)
+}
+
+/// 只订阅指定名称的手势事件
+pub fn subscribe_gesture_named(name: &str, cb: GestureCallback) -> SubscriptionId {
+    DEFAULT.subscribe_gesture_named(name, cb)
+}
 
-/// 拖拽结束回调
-static DRAG_END_CALLBACK: LazyLock<Mutex<Option<DragEndCallback>>> =
-    LazyLock::new(|| Mutex::new(None));
-
-fn callback(event_type: CGEventType, location: CGPoint) {
-    let mut state = STATE.lock().unwrap();
-
-    match event_type {
-        CGEventType::LeftMouseDown => {
-            state.reset();
-        }
-        CGEventType::LeftMouseUp => {
-            if state.has_dragging() {
-                let data = get_drag_pasteboard_data();
-                if let Some(ref cb) = *DRAG_END_CALLBACK.lock().unwrap() {
-                    cb(data);
-                }
-                // 重置拖拽状态
-                state.reset();
-            }
-        }
-        CGEventType::LeftMouseDragged => {
-            let x = location.x;
-            let y = location.y;
-            // 添加鼠标位置，检测摇动
-            state.add_position(x, y);
-
-            // 检查拖拽时粘贴板是否有变化
-            if state.check_pasteboard_change() {
-                // 标记拖拽已开始
-                state.mark_dragging_started();
-                let data = get_drag_pasteboard_data();
-                if let Some(ref cb) = *PASTEBOARD_CALLBACK.lock().unwrap() {
-                    cb(data);
-                }
-            }
-
-            // 检查是否检测到摇动且当前拖拽中还未处理
-            if state.is_shaking() && !state.is_shake_detected_in_current_drag() {
-                let data = get_drag_pasteboard_data();
-                if let Some(ref cb) = *SHAKE_CALLBACK.lock().unwrap() {
-                    cb(data);
-                }
-                state.set_shake_detected_in_current_drag(true);
-            }
-        }
-        _ => {}
-    }
+/// 注册额外的手势识别器，同名的识别器被替换，名称与内置摇动相同时不注册并返回 `false`
+pub fn register_recognizer(name: &str, recognizer: Box<dyn GestureRecognizer>) -> bool {
+    DEFAULT.register_recognizer(name, recognizer)
+}
+
+/// 移除手势识别器，返回识别器是否存在
+pub fn unregister_recognizer(name: &str) -> bool {
+    DEFAULT.unregister_recognizer(name)
+}
+
+/// 订阅拖拽结束，可以有多个订阅者
+pub fn subscribe_drag_end(cb: DragEndCallback) -> SubscriptionId {
+    DEFAULT.subscribe_drag_end(cb)
+}
+
+/// 取消订阅，返回订阅是否存在
+pub fn unsubscribe(id: SubscriptionId) -> bool {
+    DEFAULT.unsubscribe(id)
+}
+
+/// 获取拖拽事件流，可以在异步代码中逐个等待事件
+#[cfg(feature = "async")]
+pub fn drag_events() -> event_stream::DragEventStream {
+    DEFAULT.drag_events()
+}
+
+/// 设置指针事件的投递方式，对之后启动的监听生效
+///
+/// 默认使用有界队列，后端回调只负责入队，订阅者的回调运行在独立的处理线程上。
+pub fn set_event_delivery(delivery: EventDelivery) {
+    DEFAULT.set_event_delivery(delivery);
+}
+
+/// 因事件队列已满而被丢弃的指针事件总数
+pub fn dropped_event_count() -> u64 {
+    DEFAULT.dropped_event_count()
+}
+
+/// 获取当前的摇动检测阈值
+pub fn shake_config() -> ShakeConfig {
+    DEFAULT.shake_config()
+}
+
+/// 修改摇动检测阈值，立即对正在进行的拖拽生效
+pub fn set_shake_config(config: ShakeConfig) {
+    DEFAULT.set_shake_config(config);
+}
+
+/// 设置拖拽粘贴板，替换当前平台默认的实现
+pub fn set_drag_pasteboard(pasteboard: Box<dyn DragPasteboard>) {
+    DEFAULT.set_drag_pasteboard(pasteboard);
+}
+
+/// 使用指定的指针后端开始监听鼠标事件，返回的句柄被丢弃时停止监听
+pub fn start_listening_with<B: PointerBackend>(
+    backend: B,
+) -> Result<ListenerHandle, ListenerError> {
+    DEFAULT.start_listening_with(backend)
 }
 
 /// 开始监听鼠标事件
-pub fn start_listening() {
-    mouse_listener::start_listening(callback);
+///
+/// 事件监听安装在当前线程的运行循环上，可以调用 [`ListenerHandle::wait`] 运行它。
+#[cfg(target_os = "macos")]
+pub fn start_listening() -> Result<ListenerHandle, ListenerError> {
+    DEFAULT.start_listening()
+}
+
+/// 开始监听鼠标事件，连接 `DISPLAY` 指定的 X11 显示
+#[cfg(all(target_os = "linux", feature = "x11"))]
+pub fn start_listening() -> Result<ListenerHandle, ListenerError> {
+    DEFAULT.start_listening()
+}
+
+/// 在独立线程中使用指定的指针后端监听鼠标事件，后端初始化完成后返回
+pub fn spawn_listener_with<B: PointerBackend + Send + 'static>(
+    backend: B,
+) -> Result<SpawnedListener, ListenerError> {
+    DEFAULT.spawn_listener_with(backend)
+}
+
+/// 在独立线程中开始监听鼠标事件，事件监听安装完成后返回
+#[cfg(any(target_os = "macos", all(target_os = "linux", feature = "x11")))]
+pub fn spawn_listener() -> Result<SpawnedListener, ListenerError> {
+    DEFAULT.spawn_listener()
 }
 
 /// 获取鼠标当前位置
+#[cfg(target_os = "macos")]
 pub fn get_current_mouse_location() -> (f64, f64) {
-    let point = mouse_listener::get_current_mouse_location();
+    let point = macos::mouse_listener::get_current_mouse_location();
     (point.x, point.y)
 }
 
 /// 获取当前拖拽粘贴板内容
 pub fn get_drag_pasteboard_data() -> Option<DragData> {
-    macos::pasteboard_utils::get_drag_pasteboard_data()
+    DEFAULT.drag_pasteboard_data()
 }
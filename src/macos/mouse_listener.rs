@@ -1,19 +1,70 @@
 #![allow(improper_ctypes)]
 #![allow(improper_ctypes_definitions)]
 
-use core_graphics::event::{CGEvent, CGEventTapLocation, CGEventType};
+use core_graphics::event::{CGEvent, CGEventRef, CGEventTapLocation, CGEventType};
 use core_graphics::geometry::CGPoint;
 use std::os::raw::c_void;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use cocoa::base::id;
 use crate::LazyLock;
-
-// 定义回调类型
-pub type MouseEventCallback = fn(CGEventType, CGPoint);
+use crate::backend::{ListenerControl, ListenerError, ListenerHandle, PointerBackend, PointerEventCallback, StopSignal};
+use crate::model::pointer_event::{MouseButton, Point, PointerEvent};
 
 // 事件处理回调函数
 static CURRENT_MOUSE_LOCATION: LazyLock<Mutex<CGPoint>> = LazyLock::new(|| Mutex::new(CGPoint { x: 0.0, y: 0.0 }));
 
+/// 基于 CGEventTap 的 macOS 指针后端
+#[derive(Debug, Default)]
+pub struct MacPointerBackend;
+
+impl PointerBackend for MacPointerBackend {
+    fn start_listening(&mut self, callback: PointerEventCallback) -> Result<ListenerHandle, ListenerError> {
+        start_listening(callback).map(ListenerHandle::new)
+    }
+
+    fn current_location(&self) -> Point {
+        let point = get_current_mouse_location();
+        Point { x: point.x, y: point.y }
+    }
+}
+
+// 将 CoreGraphics 事件转换为平台无关的指针事件，时间戳为 mach_absolute_time 的计时单位
+fn to_pointer_event(event_type: CGEventType, location: CGPoint, timestamp: u64) -> Option<PointerEvent> {
+    let button = MouseButton::Left;
+    let location = Point { x: location.x, y: location.y };
+    let timestamp = Some(mach_ticks_to_duration(timestamp));
+    match event_type {
+        CGEventType::LeftMouseDown => Some(PointerEvent::Down { button, location, timestamp }),
+        CGEventType::LeftMouseDragged => Some(PointerEvent::Dragged { button, location, timestamp }),
+        CGEventType::LeftMouseUp => Some(PointerEvent::Up { button, location, timestamp }),
+        _ => None,
+    }
+}
+
+// mach_absolute_time 计时单位与纳秒的换算比例
+#[repr(C)]
+struct MachTimebaseInfo {
+    numer: u32,
+    denom: u32,
+}
+
+// 将 mach_absolute_time 的计时单位换算为时长，Intel 上一个单位为 1 纳秒，Apple Silicon 上约为 41.67 纳秒
+fn mach_ticks_to_duration(ticks: u64) -> Duration {
+    static TIMEBASE: LazyLock<(u128, u128)> = LazyLock::new(|| {
+        let mut info = MachTimebaseInfo { numer: 0, denom: 0 };
+        let status = unsafe { mach_timebase_info(&mut info) };
+        if status != 0 || info.denom == 0 {
+            (1, 1)
+        } else {
+            (info.numer.into(), info.denom.into())
+        }
+    });
+    let (numer, denom) = *TIMEBASE;
+    Duration::from_nanos((u128::from(ticks) * numer / denom) as u64)
+}
+
 // 在事件回调中添加坐标更新
 unsafe extern "C" fn event_callback(
     _proxy: id,
@@ -27,10 +78,13 @@ unsafe extern "C" fn event_callback(
         if user_info.is_null() {
             panic!("回调函数指针为空");
         }
-        &mut *(user_info as *mut MouseEventCallback)
+        &*(user_info as *const PointerEventCallback)
     };
     let location = cg_event.location();
-    callback(event_type, location);
+    let timestamp = unsafe { CGEventGetTimestamp(&cg_event) };
+    if let Some(event) = to_pointer_event(event_type, location, timestamp) {
+        callback(event);
+    }
     
     {
         let mut loc = CURRENT_MOUSE_LOCATION.lock().unwrap();
@@ -47,13 +101,73 @@ pub fn get_current_mouse_location() -> CGPoint {
     CGPoint { x: loc.x, y: loc.y }
 }
 
-// 开始监听鼠标事件
-pub fn start_listening(callback: MouseEventCallback) {
+/// 安装在某个线程运行循环上的 CGEventTap
+pub struct MacListener {
+    tap: *const c_void,
+    run_loop_source: id,
+    run_loop: id,
+    // 堆上的回调，地址作为 user_info 传给 CGEventTapCreate，监听器释放时一并释放
+    callback: *mut PointerEventCallback,
+    // 是否正在 wait 中运行运行循环，只有这种情况下停止时才停止运行循环
+    running: AtomicBool,
+    signal: StopSignal,
+}
+
+// CoreFoundation 对象可以跨线程移除和停止，回调只在运行循环所在线程上执行
+unsafe impl Send for MacListener {}
+unsafe impl Sync for MacListener {}
+
+impl ListenerControl for MacListener {
+    fn stop(&self) {
+        if self.signal.stop() {
+            return;
+        }
+        unsafe {
+            CGEventTapEnable(self.tap, false);
+            CFRunLoopRemoveSource(self.run_loop, self.run_loop_source, kCFRunLoopCommonModes);
+            CFMachPortInvalidate(self.tap);
+            if self.running.load(Ordering::SeqCst) {
+                CFRunLoopStop(self.run_loop);
+            }
+        }
+    }
+
+    fn wait(&self) {
+        // 不在安装事件监听的线程上时，事件由该线程自己的运行循环处理，这里只需等待停止
+        if unsafe { CFRunLoopGetCurrent() } != self.run_loop {
+            self.signal.wait();
+            return;
+        }
+        self.running.store(true, Ordering::SeqCst);
+        while !self.signal.is_stopped() {
+            unsafe { CFRunLoopRun() };
+        }
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Drop for MacListener {
+    fn drop(&mut self) {
+        self.stop();
+        unsafe {
+            CFRelease(self.run_loop_source);
+            CFRelease(self.tap as id);
+            CFRelease(self.run_loop);
+            drop(Box::from_raw(self.callback));
+        }
+    }
+}
+
+// 在当前线程的运行循环上安装事件监听，需要运行该运行循环才能收到事件
+pub fn start_listening(callback: PointerEventCallback) -> Result<MacListener, ListenerError> {
     unsafe {
         let event_mask = (1 << CGEventType::LeftMouseDown as u64)
             | (1 << CGEventType::LeftMouseDragged as u64)
             | (1 << CGEventType::LeftMouseUp as u64);
 
+        // 回调放到堆上，保证在监听器释放前地址始终有效
+        let callback = Box::into_raw(Box::new(callback));
+
         // 创建事件监听
         let tap = CGEventTapCreate(
             CGEventTapLocation::HID,
@@ -61,11 +175,12 @@ pub fn start_listening(callback: MouseEventCallback) {
             1, // CGEventTapOption::ListenOnly
             event_mask,
             Some(event_callback),
-            &callback as *const _ as *mut c_void,
+            callback as *mut c_void,
         );
 
         if tap.is_null() {
-            panic!("Failed to create event tap");
+            drop(Box::from_raw(callback));
+            return Err(ListenerError::EventTapCreation);
         }
 
         // 创建运行循环源
@@ -76,8 +191,9 @@ pub fn start_listening(callback: MouseEventCallback) {
         );
 
         // 将运行循环源添加到当前运行循环
+        let run_loop = CFRetain(CFRunLoopGetCurrent());
         CFRunLoopAddSource(
-            CFRunLoopGetCurrent(),
+            run_loop,
             run_loop_source,
             kCFRunLoopCommonModes,
         );
@@ -85,8 +201,14 @@ pub fn start_listening(callback: MouseEventCallback) {
         // 启用事件监听
         CGEventTapEnable(tap, true);
 
-        // 运行运行循环
-        CFRunLoopRun();
+        Ok(MacListener {
+            tap,
+            run_loop_source,
+            run_loop,
+            callback,
+            running: AtomicBool::new(false),
+            signal: StopSignal::default(),
+        })
     }
 }
 
@@ -107,8 +229,19 @@ unsafe extern "C" {
         order: u64,
     ) -> id;
     pub fn CFRunLoopAddSource(rl: id, source: id, mode: id);
+    pub fn CFRunLoopRemoveSource(rl: id, source: id, mode: id);
+    pub fn CFMachPortInvalidate(port: *const c_void);
+    pub fn CFRunLoopStop(rl: id);
+    pub fn CFRetain(cf: id) -> id;
+    pub fn CFRelease(cf: id);
     pub fn CFRunLoopGetCurrent() -> id;
     pub fn CGEventTapEnable(tap: *const c_void, enable: bool);
+    pub fn CGEventGetTimestamp(event: &CGEventRef) -> u64;
     pub fn CFRunLoopRun();
     pub static kCFRunLoopCommonModes: id;
 }
+
+// 位于 libSystem 中，无需额外链接
+unsafe extern "C" {
+    fn mach_timebase_info(info: *mut MachTimebaseInfo) -> i32;
+}
@@ -9,12 +9,27 @@ use objc::runtime::Object;
 use objc::{class, msg_send, sel, sel_impl};
 
 use crate::model::drag_types::{DragData, RemoteImage, RichContent};
+use crate::pasteboard::DragPasteboard;
 
 #[link(name = "AppKit", kind = "framework")]
 unsafe extern "C" {
     pub static NSPasteboardNameDrag: *const Object;
 }
 
+/// 基于 NSPasteboard 拖拽粘贴板的实现
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MacDragPasteboard;
+
+impl DragPasteboard for MacDragPasteboard {
+    fn change_count(&self) -> i64 {
+        get_drag_pasteboard_change_count()
+    }
+
+    fn data(&self) -> Option<DragData> {
+        get_drag_pasteboard_data()
+    }
+}
+
 // 获取拖拽粘贴板的 changeCount
 pub fn get_drag_pasteboard_change_count() -> i64 {
     unsafe {
@@ -0,0 +1,179 @@
+use std::path::PathBuf;
+
+use crate::model::drag_types::{DragData, RemoteImage, RichContent};
+
+// 拖拽内容的类别，各平台后端负责把它映射为自己的目标类型
+pub(crate) enum MimeKind {
+    // text/uri-list
+    UriList,
+    // text/html
+    Html,
+    // UTF8_STRING、text/plain 等纯文本类型
+    PlainText,
+}
+
+// 按与 NSPasteboard 相同的优先级把拖拽内容映射为 DragData
+pub(crate) fn resolve_drag_data(
+    mut read_text: impl FnMut(MimeKind) -> Option<String>,
+) -> Option<DragData> {
+    // 处理文件类型数据
+    if let Some(uri_list) = read_text(MimeKind::UriList) {
+        let paths = parse_file_uri_list(&uri_list);
+        if !paths.is_empty() {
+            return Some(DragData::LocalFile(paths));
+        }
+    }
+
+    // 处理富文本类型数据（HTML）
+    if let Some(html_str) = read_text(MimeKind::Html) {
+        let plain_str = read_text(MimeKind::PlainText).unwrap_or_default();
+
+        // 远程图片识别逻辑
+        let is_single_image = html_str.contains("<img")
+            && (plain_str.starts_with("http://") || plain_str.starts_with("https://"));
+
+        if is_single_image {
+            return Some(DragData::RemoteImage(vec![RemoteImage { url: plain_str }]));
+        }
+
+        return Some(DragData::RichText(RichContent {
+            html: html_str,
+            plain_text_fallback: plain_str,
+        }));
+    }
+
+    // 处理纯文本类型数据
+    read_text(MimeKind::PlainText).map(DragData::PlainText)
+}
+
+// 解码拖拽文本，浏览器提供的 text/html 可能是 UTF-16，有时不带 BOM
+pub(crate) fn decode_text(bytes: &[u8]) -> String {
+    let utf16 = |rest: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = rest.chunks_exact(2).map(|c| from([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+    // 去掉末尾的 NUL 后判断文本中间是否有 NUL
+    let content = bytes
+        .iter()
+        .rposition(|b| *b != 0)
+        .map_or(&[][..], |end| &bytes[..=end]);
+    let text = match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        // UTF-8 文本中间不会出现 NUL，出现时按 UTF-16 解码，零字节多在奇数位上的是小端序
+        _ if content.contains(&0) => {
+            let odd_zeros = bytes.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+            let even_zeros = bytes.iter().step_by(2).filter(|b| **b == 0).count();
+            if odd_zeros >= even_zeros {
+                utf16(bytes, u16::from_le_bytes)
+            } else {
+                utf16(bytes, u16::from_be_bytes)
+            }
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    };
+    text.trim_end_matches('\0').to_owned()
+}
+
+// 解析 text/uri-list，只保留 file:// 形式的本地路径
+fn parse_file_uri_list(uri_list: &str) -> Vec<PathBuf> {
+    uri_list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|uri| uri.strip_prefix("file://"))
+        // 跳过主机名部分，例如 file://hostname/path
+        .filter_map(|rest| rest.find('/').map(|index| &rest[index..]))
+        .map(|path| PathBuf::from(percent_decode(path)))
+        .collect()
+}
+
+// 解码 URI 中的百分号转义
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        // 只接受两位十六进制数字，`from_str_radix` 还会接受前导的 `+`
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok());
+        if bytes[i] == b'%'
+            && let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        text.encode_utf16().flat_map(to_bytes).collect()
+    }
+
+    #[test]
+    fn uri_list_keeps_only_local_files() {
+        let uri_list = "# dragged from a file manager\r\n\
+                        file:///home/user/a%20b.txt\r\n\
+                        \r\n\
+                        file://hostname/srv/%E6%96%87%E4%BB%B6.png\r\n\
+                        https://example.com/image.png\r\n\
+                        file://localhost\r\n\
+                        ftp://host/file";
+        assert_eq!(
+            parse_file_uri_list(uri_list),
+            [
+                PathBuf::from("/home/user/a b.txt"),
+                PathBuf::from("/srv/文件.png")
+            ]
+        );
+        assert!(parse_file_uri_list("# only a comment\nhttp://host/a").is_empty());
+    }
+
+    #[test]
+    fn percent_decode_requires_two_hex_digits() {
+        assert_eq!(percent_decode("a%2Fb%2fc"), "a/b/c");
+        assert_eq!(percent_decode("%+a%-1%g0"), "%+a%-1%g0");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%4"), "%4");
+        assert_eq!(percent_decode("%e6%96%87"), "文");
+        // 解码出的字节不是合法的 UTF-8 时替换为 U+FFFD
+        assert_eq!(percent_decode("%FF"), "\u{FFFD}");
+    }
+
+    #[test]
+    fn decode_text_handles_utf8_and_utf16() {
+        assert_eq!(decode_text("拖拽 text".as_bytes()), "拖拽 text");
+        assert_eq!(decode_text(b"text\0\0"), "text");
+
+        let html = "<b>拖拽</b>";
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(utf16(html, u16::to_le_bytes));
+        assert_eq!(decode_text(&le), html);
+        let mut be = vec![0xFE, 0xFF];
+        be.extend(utf16(html, u16::to_be_bytes));
+        assert_eq!(decode_text(&be), html);
+
+        // 不带 BOM 的 UTF-16 按零字节所在的位置判断字节序
+        assert_eq!(decode_text(&utf16(html, u16::to_le_bytes)), html);
+        assert_eq!(decode_text(&utf16(html, u16::to_be_bytes)), html);
+
+        // 末尾的 NUL 终止符被去掉
+        let mut terminated = utf16(html, u16::to_le_bytes);
+        terminated.extend([0, 0]);
+        assert_eq!(decode_text(&terminated), html);
+        let mut with_bom = vec![0xFF, 0xFE];
+        with_bom.extend(utf16("a\0", u16::to_le_bytes));
+        assert_eq!(decode_text(&with_bom), "a");
+        assert_eq!(decode_text(&[]), "");
+    }
+}
@@ -0,0 +1,505 @@
+use std::sync::{Arc, Mutex};
+
+use crate::backend::{
+    ListenerError, ListenerHandle, PointerBackend, PointerEventCallback, StopSignal, ThreadListener,
+};
+use crate::model::drag_types::DragData;
+use crate::model::pointer_event::{MouseButton, Point, PointerEvent};
+use crate::pasteboard::DragPasteboard;
+
+/// 可脚本化的指针后端，在没有真实输入设备的环境中驱动拖拽状态机
+///
+/// `start_listening` 只记录回调并立即返回，之后通过 `press`、`drag_to`、`release`
+/// 等方法在调用线程上调用后端回调。克隆出的实例共享同一个回调，停止监听后不再投递事件。
+///
+/// 事件何时被状态机处理取决于实例的投递方式：默认的队列投递只在回调中入队，订阅者的回调
+/// 稍后在处理线程上运行。在调用之后立即检查回调结果的测试应以
+/// [`EventDelivery::Synchronous`](crate::event_queue::EventDelivery::Synchronous) 构建实例，
+/// 或者先停止监听，等待队列中的事件处理完。
+#[derive(Clone, Default)]
+pub struct MockPointerBackend {
+    inner: Arc<Mutex<MockPointerInner>>,
+}
+
+#[derive(Default)]
+struct MockPointerInner {
+    callback: Option<PointerEventCallback>,
+    location: Point,
+}
+
+impl MockPointerBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 发送任意指针事件，未在监听时发送的事件会被丢弃
+    ///
+    /// 以下几个便捷方法发送的事件不带时间戳，需要确定的时间时通过该方法指定 `timestamp`。
+    pub fn emit(&self, event: PointerEvent) {
+        let callback = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.location = event.location();
+            inner.callback.clone()
+        };
+        // 回调期间不持有内部锁，避免回调中再次访问后端时死锁
+        if let Some(callback) = callback {
+            callback(event);
+        }
+    }
+
+    /// 在指定位置按下鼠标左键
+    pub fn press(&self, x: f64, y: f64) {
+        self.emit(PointerEvent::Down {
+            button: MouseButton::Left,
+            location: Point { x, y },
+            timestamp: None,
+        });
+    }
+
+    /// 按住鼠标左键拖动到指定位置
+    pub fn drag_to(&self, x: f64, y: f64) {
+        self.emit(PointerEvent::Dragged {
+            button: MouseButton::Left,
+            location: Point { x, y },
+            timestamp: None,
+        });
+    }
+
+    /// 在指定位置抬起鼠标左键
+    pub fn release(&self, x: f64, y: f64) {
+        self.emit(PointerEvent::Up {
+            button: MouseButton::Left,
+            location: Point { x, y },
+            timestamp: None,
+        });
+    }
+
+    /// 是否已经开始监听
+    pub fn is_listening(&self) -> bool {
+        self.inner.lock().unwrap().callback.is_some()
+    }
+}
+
+impl PointerBackend for MockPointerBackend {
+    fn start_listening(
+        &mut self,
+        callback: PointerEventCallback,
+    ) -> Result<ListenerHandle, ListenerError> {
+        self.inner.lock().unwrap().callback = Some(callback);
+        let inner = self.inner.clone();
+        Ok(ListenerHandle::new(ThreadListener::new(
+            Arc::new(StopSignal::default()),
+            move || {
+                inner.lock().unwrap().callback = None;
+            },
+        )))
+    }
+
+    fn current_location(&self) -> Point {
+        self.inner.lock().unwrap().location
+    }
+}
+
+/// 可脚本化的拖拽粘贴板，每次写入内容都会递增变化计数
+#[derive(Clone, Default)]
+pub struct MockPasteboard {
+    inner: Arc<Mutex<MockPasteboardInner>>,
+}
+
+#[derive(Default)]
+struct MockPasteboardInner {
+    change_count: i64,
+    data: Option<DragData>,
+}
+
+impl MockPasteboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入拖拽数据并递增变化计数，模拟开始拖拽新内容
+    pub fn set_data(&self, data: Option<DragData>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.change_count += 1;
+        inner.data = data;
+    }
+
+    /// 清空拖拽数据并递增变化计数
+    pub fn clear(&self) {
+        self.set_data(None);
+    }
+}
+
+impl DragPasteboard for MockPasteboard {
+    fn change_count(&self) -> i64 {
+        self.inner.lock().unwrap().change_count
+    }
+
+    fn data(&self) -> Option<DragData> {
+        self.inner.lock().unwrap().data.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::Dorazu;
+    use crate::event_queue::EventDelivery;
+    use crate::model::shake_event::ShakeAxis;
+    use crate::shake_config::ShakeConfig;
+
+    // 以同步投递构建的实例及其模拟后端，回调在调用模拟方法时就已经运行
+    struct Session {
+        dorazu: Dorazu,
+        backend: MockPointerBackend,
+        pasteboard: MockPasteboard,
+        log: Arc<Mutex<Vec<String>>>,
+        _listener: ListenerHandle,
+    }
+
+    impl Session {
+        fn new(shake_config: ShakeConfig) -> Self {
+            let pasteboard = MockPasteboard::new();
+            let dorazu = Dorazu::builder()
+                .pasteboard(Box::new(pasteboard.clone()))
+                .event_delivery(EventDelivery::Synchronous)
+                .shake_config(shake_config)
+                .build();
+            let backend = MockPointerBackend::new();
+            let listener = dorazu.start_listening_with(backend.clone()).unwrap();
+            Self {
+                dorazu,
+                backend,
+                pasteboard,
+                log: Arc::new(Mutex::new(Vec::new())),
+                _listener: listener,
+            }
+        }
+
+        // 把回调记入日志
+        fn logger<T: 'static>(&self, describe: fn(T) -> String) -> Box<dyn Fn(T) + Send + Sync> {
+            let log = self.log.clone();
+            Box::new(move |value| log.lock().unwrap().push(describe(value)))
+        }
+
+        // 订阅全部回调，返回各个订阅的编号
+        fn subscribe_all(&self) -> [crate::subscription::SubscriptionId; 4] {
+            [
+                self.dorazu.subscribe_pasteboard_changed(
+                    self.logger(|data| format!("changed {}", text(&data))),
+                ),
+                self.dorazu.subscribe_shake(self.logger(|event| {
+                    format!(
+                        "shake {} {:?} {}",
+                        event.ordinal,
+                        event.axis,
+                        text(&event.data)
+                    )
+                })),
+                self.dorazu.subscribe_shake_ended(self.logger(|event| {
+                    format!("shake ended {} {}", event.ordinal, text(&event.data))
+                })),
+                self.dorazu
+                    .subscribe_drag_end(self.logger(|data| format!("end {}", text(&data)))),
+            ]
+        }
+
+        fn take_log(&self) -> Vec<String> {
+            std::mem::take(&mut *self.log.lock().unwrap())
+        }
+
+        // 在指定时间拖动到指定位置
+        fn drag_at(&self, x: f64, y: f64, ms: u64) {
+            self.backend.emit(PointerEvent::Dragged {
+                button: MouseButton::Left,
+                location: Point { x, y },
+                timestamp: Some(Duration::from_millis(ms)),
+            });
+        }
+
+        // 从 `start_ms` 开始每隔 20 毫秒水平来回移动，返回最后一个事件的时间
+        fn shake(&self, count: u64, start_ms: u64) -> u64 {
+            for index in 0..count {
+                let x = if index % 2 == 0 { 50.0 } else { 0.0 };
+                self.drag_at(x, 0.0, start_ms + index * 20);
+            }
+            start_ms + (count - 1) * 20
+        }
+
+        fn release_at(&self, x: f64, ms: u64) {
+            self.backend.emit(PointerEvent::Up {
+                button: MouseButton::Left,
+                location: Point { x, y: 0.0 },
+                timestamp: Some(Duration::from_millis(ms)),
+            });
+        }
+    }
+
+    fn text(data: &Option<DragData>) -> String {
+        match data {
+            Some(DragData::PlainText(text)) => text.clone(),
+            other => format!("{other:?}"),
+        }
+    }
+
+    fn plain(text: &str) -> Option<DragData> {
+        Some(DragData::PlainText(text.to_owned()))
+    }
+
+    #[test]
+    fn drag_reports_change_shake_and_end_with_data() {
+        let session = Session::new(ShakeConfig::DEFAULT);
+        session.subscribe_all();
+
+        session.backend.press(0.0, 0.0);
+        session.pasteboard.set_data(plain("hello"));
+        let last = session.shake(10, 0);
+        session.release_at(0.0, last + 20);
+
+        assert_eq!(
+            session.take_log(),
+            [
+                "changed hello",
+                "shake 1 Horizontal hello",
+                "shake ended 1 hello",
+                "end hello",
+            ]
+        );
+    }
+
+    #[test]
+    fn drag_without_pasteboard_change_reports_nothing() {
+        let session = Session::new(ShakeConfig::DEFAULT);
+        session.subscribe_all();
+        session.pasteboard.set_data(plain("before"));
+
+        // 按下之前的变化不算作这次拖拽的开始
+        session.backend.press(0.0, 0.0);
+        let last = session.shake(10, 0);
+        session.release_at(0.0, last + 20);
+
+        assert!(session.take_log().is_empty());
+        assert_eq!(session.backend.current_location(), Point { x: 0.0, y: 0.0 });
+    }
+
+    #[test]
+    fn vertical_shake_reports_axis() {
+        let session = Session::new(ShakeConfig::DEFAULT);
+        let axes = Arc::new(Mutex::new(Vec::new()));
+        {
+            let axes = axes.clone();
+            session.dorazu.subscribe_shake_axis(
+                ShakeAxis::Horizontal,
+                Box::new(move |event| axes.lock().unwrap().push(event.axis)),
+            );
+        }
+        session.subscribe_all();
+
+        session.backend.press(0.0, 0.0);
+        session.pasteboard.set_data(plain("up and down"));
+        for index in 0..10 {
+            session.drag_at(0.0, if index % 2 == 0 { 50.0 } else { 0.0 }, index * 20);
+        }
+        session.release_at(0.0, 200);
+
+        assert_eq!(session.take_log()[1], "shake 1 Vertical up and down");
+        assert!(axes.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn unsubscribe_stops_only_that_subscriber() {
+        let session = Session::new(ShakeConfig::DEFAULT);
+        let [changed, shake, shake_ended, drag_end] = session.subscribe_all();
+        let kept = session
+            .dorazu
+            .subscribe_drag_end(session.logger(|data| format!("kept end {}", text(&data))));
+
+        assert!(session.dorazu.unsubscribe(changed));
+        assert!(session.dorazu.unsubscribe(drag_end));
+        assert!(!session.dorazu.unsubscribe(drag_end));
+
+        session.backend.press(0.0, 0.0);
+        session.pasteboard.set_data(plain("first"));
+        let last = session.shake(10, 0);
+        session.release_at(0.0, last + 20);
+        assert_eq!(
+            session.take_log(),
+            [
+                "shake 1 Horizontal first",
+                "shake ended 1 first",
+                "kept end first"
+            ]
+        );
+
+        for id in [shake, shake_ended, kept] {
+            assert!(session.dorazu.unsubscribe(id));
+        }
+        session.backend.press(0.0, 0.0);
+        session.pasteboard.set_data(plain("second"));
+        session.backend.drag_to(10.0, 0.0);
+        session.backend.release(10.0, 0.0);
+        assert!(session.take_log().is_empty());
+    }
+
+    #[test]
+    fn set_callback_replaces_only_the_previous_callback() {
+        let session = Session::new(ShakeConfig::DEFAULT);
+        session
+            .dorazu
+            .set_drag_end_callback(session.logger(|_| "old".to_owned()));
+        session
+            .dorazu
+            .set_drag_end_callback(session.logger(|_| "new".to_owned()));
+        session
+            .dorazu
+            .subscribe_drag_end(session.logger(|_| "subscriber".to_owned()));
+
+        session.backend.press(0.0, 0.0);
+        session.pasteboard.clear();
+        session.backend.drag_to(10.0, 0.0);
+        session.backend.release(10.0, 0.0);
+
+        let mut log = session.take_log();
+        log.sort();
+        assert_eq!(log, ["new", "subscriber"]);
+    }
+
+    #[test]
+    fn repeated_shakes_respect_cooldown_and_end_in_order() {
+        let session = Session::new(ShakeConfig {
+            repeat_cooldown: Some(Duration::from_millis(300)),
+            ..ShakeConfig::DEFAULT
+        });
+        session.subscribe_all();
+
+        session.backend.press(0.0, 0.0);
+        session.pasteboard.set_data(plain("data"));
+        let last = session.shake(10, 0);
+        // 停顿超过 200 毫秒，第一次摇动结束
+        session.drag_at(100.0, 0.0, last + 250);
+        // 冷却时间内再次摇动不上报
+        let last = session.shake(10, last + 270);
+        session.drag_at(100.0, 0.0, last + 250);
+        // 冷却时间过后再次摇动，作为第二次摇动上报，拖拽结束时先结束它
+        let last = session.shake(10, last + 700);
+        session.release_at(0.0, last + 20);
+
+        assert_eq!(
+            session.take_log(),
+            [
+                "changed data",
+                "shake 1 Horizontal data",
+                "shake ended 1 data",
+                "shake 2 Horizontal data",
+                "shake ended 2 data",
+                "end data",
+            ]
+        );
+    }
+
+    #[test]
+    fn pasteboard_change_ends_active_shake_first() {
+        let session = Session::new(ShakeConfig::DEFAULT);
+        session.subscribe_all();
+
+        session.backend.press(0.0, 0.0);
+        session.pasteboard.set_data(plain("first"));
+        let last = session.shake(10, 0);
+        // 摇动过程中开始拖拽新的内容，序号随识别状态一起重新开始
+        session.pasteboard.set_data(plain("second"));
+        let last = session.shake(10, last + 20);
+        session.release_at(0.0, last + 20);
+
+        assert_eq!(
+            session.take_log(),
+            [
+                "changed first",
+                "shake 1 Horizontal first",
+                "shake ended 1 second",
+                "changed second",
+                "shake 1 Horizontal second",
+                "shake ended 1 second",
+                "end second",
+            ]
+        );
+    }
+
+    #[test]
+    fn instances_run_side_by_side_with_their_own_configuration() {
+        let sensitive = Session::new(ShakeConfig::DEFAULT);
+        let strict = Session::new(ShakeConfig {
+            min_direction_changes: 20,
+            ..ShakeConfig::DEFAULT
+        });
+        sensitive.subscribe_all();
+        strict.subscribe_all();
+
+        // 两个实例交替收到同样的移动，只有阈值较低的实例识别出摇动
+        let drag_both = |sensitive_text: &str, strict_text: &str| {
+            sensitive.backend.press(0.0, 0.0);
+            strict.backend.press(0.0, 0.0);
+            sensitive.pasteboard.set_data(plain(sensitive_text));
+            strict.pasteboard.set_data(plain(strict_text));
+            for index in 0..10 {
+                let x = if index % 2 == 0 { 50.0 } else { 0.0 };
+                sensitive.drag_at(x, 0.0, index * 20);
+                strict.drag_at(x, 0.0, index * 20);
+            }
+            sensitive.release_at(0.0, 200);
+            strict.release_at(0.0, 200);
+        };
+        drag_both("a", "b");
+        assert_eq!(
+            sensitive.take_log(),
+            [
+                "changed a",
+                "shake 1 Horizontal a",
+                "shake ended 1 a",
+                "end a",
+            ]
+        );
+        assert_eq!(strict.take_log(), ["changed b", "end b"]);
+
+        // 重置一个实例不影响另一个实例的订阅
+        sensitive.dorazu.reset();
+        drag_both("c", "d");
+        assert!(sensitive.take_log().is_empty());
+        assert_eq!(strict.take_log(), ["changed d", "end d"]);
+    }
+
+    #[test]
+    fn reset_restores_the_default_instance() {
+        let pasteboard = MockPasteboard::new();
+        crate::set_event_delivery(EventDelivery::Synchronous);
+        crate::set_shake_config(ShakeConfig::TRACKPAD);
+        crate::set_drag_pasteboard(Box::new(pasteboard.clone()));
+        let circle = crate::gesture::CircleDetector::new(Default::default());
+        assert!(crate::register_recognizer("circle", Box::new(circle)));
+        let log = Arc::new(Mutex::new(Vec::new()));
+        {
+            let log = log.clone();
+            crate::subscribe_drag_end(Box::new(move |data| log.lock().unwrap().push(data)));
+        }
+        let backend = MockPointerBackend::new();
+        let _listener = crate::start_listening_with(backend.clone()).unwrap();
+        let drag = || {
+            backend.press(0.0, 0.0);
+            pasteboard.set_data(plain("data"));
+            backend.drag_to(10.0, 0.0);
+            backend.release(10.0, 0.0);
+        };
+        drag();
+        assert_eq!(log.lock().unwrap().len(), 1);
+
+        crate::reset();
+        assert_eq!(crate::shake_config(), ShakeConfig::DEFAULT);
+        assert!(!crate::unregister_recognizer("circle"));
+        assert_eq!(crate::dropped_event_count(), 0);
+        // 重新设置粘贴板后拖拽，已清除的订阅者不再收到回调
+        crate::set_drag_pasteboard(Box::new(pasteboard.clone()));
+        drag();
+        assert_eq!(log.lock().unwrap().len(), 1);
+    }
+}
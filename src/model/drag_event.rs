@@ -0,0 +1,18 @@
+use crate::model::drag_types::DragData;
+use crate::model::gesture_event::GestureEvent;
+use crate::model::shake_event::{ShakeEndedEvent, ShakeEvent};
+
+/// 统一的拖拽生命周期事件
+#[derive(Debug, Clone)]
+pub enum DragEvent {
+    /// 拖拽粘贴板内容变化，即开始拖拽新内容
+    PasteboardChanged(Option<DragData>),
+    /// 拖拽过程中检测到鼠标摇动
+    Shake(ShakeEvent),
+    /// 摇动停止
+    ShakeEnded(ShakeEndedEvent),
+    /// 识别到额外注册的识别器的手势，内置的摇动只以 `Shake` 上报
+    Gesture(GestureEvent),
+    /// 拖拽结束
+    DragEnd(Option<DragData>),
+}
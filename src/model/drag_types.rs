@@ -1,4 +1,5 @@
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize, serde::Deserialize))]
 pub enum DragData {
     LocalFile(Vec<std::path::PathBuf>),
     RemoteImage(Vec<RemoteImage>),
@@ -7,11 +8,13 @@ pub enum DragData {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize, serde::Deserialize))]
 pub struct RemoteImage {
     pub url: String,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize, serde::Deserialize))]
 pub struct RichContent {
     pub html: String,
     pub plain_text_fallback: String,
@@ -0,0 +1,51 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::model::drag_types::DragData;
+use crate::model::shake_event::{ShakeAxis, ShakeInfo};
+
+/// 画圈的方向，以屏幕上看到的方向为准
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircleDirection {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// 手势识别器给出的识别结果
+#[derive(Debug, Clone)]
+pub enum GestureDetail {
+    /// 鼠标摇动
+    Shake {
+        /// 本次摇动在当前拖拽中的序号，从 1 开始
+        ordinal: usize,
+        axis: ShakeAxis,
+        info: ShakeInfo,
+    },
+    /// 画圈
+    Circle {
+        /// 本次画圈在当前拖拽中的序号，从 1 开始
+        ordinal: usize,
+        direction: CircleDirection,
+        /// 圈的平均半径（像素）
+        radius: f64,
+    },
+    /// 与模板匹配的单笔手势
+    Template {
+        /// 匹配到的模板名称
+        name: String,
+        /// 相似度分数，在 0 到 1 之间
+        score: f64,
+    },
+    /// 自定义识别器的结果，可以通过 `downcast_ref` 取回具体类型
+    Custom(Arc<dyn Any + Send + Sync>),
+}
+
+/// 拖拽过程中识别到手势时上报的事件
+#[derive(Debug, Clone)]
+pub struct GestureEvent {
+    /// 注册识别器时使用的名称，内置的摇动识别器为 [`SHAKE_GESTURE`](crate::gesture::SHAKE_GESTURE)
+    pub name: String,
+    pub detail: GestureDetail,
+    /// 当前拖拽的数据
+    pub data: Option<DragData>,
+}
@@ -1 +1,5 @@
-pub mod drag_types;
\ No newline at end of file
+pub mod drag_event;
+pub mod drag_types;
+pub mod gesture_event;
+pub mod pointer_event;
+pub mod shake_event;
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+/// 鼠标按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize, serde::Deserialize))]
+pub enum MouseButton {
+    Left,
+    Right,
+    Other(u32),
+}
+
+/// 屏幕坐标点
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(
+    any(feature = "trace", feature = "templates"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// 平台无关的指针事件，由各平台后端产生并交给状态机处理
+///
+/// `timestamp` 是事件源提供的时间戳，起点由后端决定，同一后端产生的时间戳可以相减。
+/// 后端无法提供时为 `None`，此时以事件被处理的时间代替。
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointerEvent {
+    Down {
+        button: MouseButton,
+        location: Point,
+        #[cfg_attr(feature = "trace", serde(default))]
+        timestamp: Option<Duration>,
+    },
+    Dragged {
+        button: MouseButton,
+        location: Point,
+        #[cfg_attr(feature = "trace", serde(default))]
+        timestamp: Option<Duration>,
+    },
+    Up {
+        button: MouseButton,
+        location: Point,
+        #[cfg_attr(feature = "trace", serde(default))]
+        timestamp: Option<Duration>,
+    },
+}
+
+impl PointerEvent {
+    // 获取事件对应的按键
+    pub fn button(&self) -> MouseButton {
+        match *self {
+            PointerEvent::Down { button, .. }
+            | PointerEvent::Dragged { button, .. }
+            | PointerEvent::Up { button, .. } => button,
+        }
+    }
+
+    // 获取事件发生的位置
+    pub fn location(&self) -> Point {
+        match *self {
+            PointerEvent::Down { location, .. }
+            | PointerEvent::Dragged { location, .. }
+            | PointerEvent::Up { location, .. } => location,
+        }
+    }
+
+    // 获取事件源提供的时间戳
+    pub fn timestamp(&self) -> Option<Duration> {
+        match *self {
+            PointerEvent::Down { timestamp, .. }
+            | PointerEvent::Dragged { timestamp, .. }
+            | PointerEvent::Up { timestamp, .. } => timestamp,
+        }
+    }
+}
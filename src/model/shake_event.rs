@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use crate::model::drag_types::DragData;
+
+/// 摇动的主要方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShakeAxis {
+    /// 左右摇动
+    Horizontal,
+    /// 上下摇动
+    Vertical,
+    /// 沿斜线来回摇动，两个方向的折返次数相近
+    Diagonal,
+}
+
+/// 摇动的强度信息，根据检测到摇动时的折返记录和位置历史计算
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShakeInfo {
+    /// 本次摇动中的折返次数
+    pub reversal_count: usize,
+    /// 每次折返前平均移动的距离（像素）
+    pub average_amplitude: f64,
+    /// 位置历史中的最大移动速度（像素每秒）
+    pub peak_velocity: f64,
+    /// 从第一次折返到检测到摇动经过的时长
+    pub duration: Duration,
+    /// 0 到 1 之间的置信度，越大表示摇动越明确
+    pub confidence: f64,
+}
+
+/// 检测到鼠标摇动时上报的事件
+#[derive(Debug, Clone)]
+pub struct ShakeEvent {
+    /// 本次摇动在当前拖拽中的序号，从 1 开始
+    pub ordinal: usize,
+    /// 摇动的主要方向
+    pub axis: ShakeAxis,
+    /// 摇动的强度
+    pub info: ShakeInfo,
+    /// 当前拖拽的数据
+    pub data: Option<DragData>,
+}
+
+/// 已上报的摇动停止时上报的事件
+#[derive(Debug, Clone)]
+pub struct ShakeEndedEvent {
+    /// 停止的摇动在当前拖拽中的序号，与对应的 [`ShakeEvent::ordinal`] 相同
+    pub ordinal: usize,
+    /// 当前拖拽的数据
+    pub data: Option<DragData>,
+}
@@ -0,0 +1,55 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::model::drag_types::DragData;
+
+/// 拖拽粘贴板，状态机通过它感知拖拽内容的变化并读取拖拽数据
+///
+/// 后端回调所在的线程读取变化计数的同时，事件处理线程可能正在读取数据，实现需要允许并发调用。
+pub trait DragPasteboard: Send + Sync {
+    /// 获取粘贴板的变化计数，内容每变化一次计数都会改变
+    fn change_count(&self) -> i64;
+
+    /// 获取粘贴板中当前的拖拽数据
+    fn data(&self) -> Option<DragData>;
+
+    /// 获取粘贴板处于指定变化计数时的拖拽数据，用于读取某个事件到达时的拖拽内容
+    ///
+    /// 默认返回当前数据。能够保留历史内容的实现（例如回放）应返回该计数下的数据，
+    /// 使排队处理的事件不会读到之后才写入的内容。
+    fn data_at(&self, change_count: i64) -> Option<DragData> {
+        let _ = change_count;
+        self.data()
+    }
+}
+
+/// 空粘贴板，计数始终为 0 且没有数据，用于没有粘贴板实现的平台
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullPasteboard;
+
+impl DragPasteboard for NullPasteboard {
+    fn change_count(&self) -> i64 {
+        0
+    }
+
+    fn data(&self) -> Option<DragData> {
+        None
+    }
+}
+
+// 打开当前平台默认的拖拽粘贴板
+pub(crate) fn default_drag_pasteboard()
+-> Result<Arc<dyn DragPasteboard>, Box<dyn Error + Send + Sync>> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(Arc::new(crate::macos::pasteboard_utils::MacDragPasteboard))
+    }
+    #[cfg(all(target_os = "linux", feature = "x11"))]
+    {
+        Ok(Arc::new(crate::x11::xdnd_selection::XdndPasteboard::new()?))
+    }
+    #[cfg(not(any(target_os = "macos", all(target_os = "linux", feature = "x11"))))]
+    {
+        Ok(Arc::new(NullPasteboard))
+    }
+}
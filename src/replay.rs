@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::backend::{
+    ListenerError, ListenerHandle, PointerBackend, PointerEventCallback, StopSignal, ThreadListener,
+};
+use crate::model::drag_types::DragData;
+use crate::model::pointer_event::{Point, PointerEvent};
+use crate::pasteboard::DragPasteboard;
+use crate::trace::{TraceRecord, read_trace};
+
+/// 回放速度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// 按录制时的时间间隔回放
+    Original,
+    /// 按倍率缩放时间间隔，2.0 表示两倍速
+    Scaled(f64),
+    /// 不等待，依次立即回放
+    ///
+    /// 使用队列投递时可能超出队列容量而丢弃事件，需要完整回放时应改用同步投递。
+    Immediate,
+}
+
+/// 回放轨迹文件的指针后端
+///
+/// 按顺序把录制的指针事件交给状态机，同时通过 [`ReplayBackend::pasteboard`]
+/// 提供与录制时一致的粘贴板变化计数和拖拽数据，从而重现相同的回调。
+/// 开始监听时该粘贴板会自动替换实例当前使用的粘贴板。
+/// 不带时间戳的事件以录制时的时间补上，回放速度不影响摇动等手势的识别结果。
+/// 回放在后台线程中进行，全部事件回放完后监听结束，可以用 [`ListenerHandle::wait`] 等待。
+pub struct ReplayBackend {
+    records: Arc<Vec<TraceRecord>>,
+    speed: ReplaySpeed,
+    cursor: Arc<Mutex<ReplayCursor>>,
+}
+
+// 回放进度，由后端和粘贴板共享
+#[derive(Default)]
+struct ReplayCursor {
+    change_count: i64,
+    location: Point,
+    // 各变化计数下录制到的拖拽数据
+    snapshots: HashMap<i64, Option<DragData>>,
+}
+
+impl ReplayBackend {
+    /// 从轨迹记录创建，默认按原始时间间隔回放
+    pub fn new(records: Vec<TraceRecord>) -> Self {
+        let snapshots = records
+            .iter()
+            .filter_map(|record| match record {
+                TraceRecord::Data {
+                    change_count, data, ..
+                } => Some((*change_count, data.clone())),
+                _ => None,
+            })
+            .collect();
+        Self {
+            records: Arc::new(records),
+            speed: ReplaySpeed::Original,
+            cursor: Arc::new(Mutex::new(ReplayCursor {
+                snapshots,
+                ..ReplayCursor::default()
+            })),
+        }
+    }
+
+    /// 读取轨迹
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        Ok(Self::new(read_trace(reader)?))
+    }
+
+    /// 打开轨迹文件
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// 设置回放速度
+    pub fn with_speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// 获取与回放进度同步的粘贴板
+    pub fn pasteboard(&self) -> ReplayPasteboard {
+        ReplayPasteboard {
+            cursor: self.cursor.clone(),
+        }
+    }
+}
+
+// 计算两条记录之间需要等待的时间
+fn replay_delay(speed: ReplaySpeed, elapsed_ms: f64) -> Option<Duration> {
+    let scale = match speed {
+        ReplaySpeed::Original => 1.0,
+        ReplaySpeed::Scaled(factor) if factor > 0.0 => factor,
+        ReplaySpeed::Scaled(_) | ReplaySpeed::Immediate => return None,
+    };
+    (elapsed_ms > 0.0).then(|| Duration::from_secs_f64(elapsed_ms / scale / 1000.0))
+}
+
+// 为不带时间戳的事件补上录制时的时间，使状态机看到的时间与回放速度无关
+fn with_recorded_timestamp(mut event: PointerEvent, elapsed_ms: f64) -> PointerEvent {
+    let (PointerEvent::Down { timestamp, .. }
+    | PointerEvent::Dragged { timestamp, .. }
+    | PointerEvent::Up { timestamp, .. }) = &mut event;
+    if timestamp.is_none() {
+        *timestamp = Some(Duration::from_secs_f64(elapsed_ms.max(0.0) / 1000.0));
+    }
+    event
+}
+
+// 按顺序回放指针事件，直到全部回放完或被停止
+fn replay(
+    records: &[TraceRecord],
+    speed: ReplaySpeed,
+    cursor: &Mutex<ReplayCursor>,
+    callback: &PointerEventCallback,
+    signal: &StopSignal,
+) {
+    let mut previous_ms = None;
+    for record in records {
+        let TraceRecord::Pointer {
+            elapsed_ms,
+            change_count,
+            event,
+        } = record
+        else {
+            continue;
+        };
+
+        let delay = previous_ms.and_then(|previous| replay_delay(speed, elapsed_ms - previous));
+        let stopped = match delay {
+            Some(delay) => signal.wait_timeout(delay),
+            None => signal.is_stopped(),
+        };
+        if stopped {
+            return;
+        }
+        previous_ms = Some(*elapsed_ms);
+
+        {
+            let mut cursor = cursor.lock().unwrap();
+            cursor.change_count = *change_count;
+            cursor.location = event.location();
+        }
+        callback(with_recorded_timestamp(*event, *elapsed_ms));
+    }
+}
+
+impl PointerBackend for ReplayBackend {
+    fn start_listening(
+        &mut self,
+        callback: PointerEventCallback,
+    ) -> Result<ListenerHandle, ListenerError> {
+        let signal = Arc::new(StopSignal::default());
+        {
+            let records = self.records.clone();
+            let speed = self.speed;
+            let cursor = self.cursor.clone();
+            let signal = signal.clone();
+            thread::spawn(move || {
+                replay(&records, speed, &cursor, &callback, &signal);
+                signal.stop();
+            });
+        }
+        // 回放线程只在等待间隔时阻塞在停止标志上，无需额外唤醒
+        Ok(ListenerHandle::new(ThreadListener::new(signal, || {})))
+    }
+
+    fn current_location(&self) -> Point {
+        self.cursor.lock().unwrap().location
+    }
+
+    fn drag_pasteboard(&self) -> Option<Box<dyn DragPasteboard>> {
+        Some(Box::new(self.pasteboard()))
+    }
+}
+
+/// 回放录制时粘贴板状态的粘贴板，由 [`ReplayBackend::pasteboard`] 创建
+pub struct ReplayPasteboard {
+    cursor: Arc<Mutex<ReplayCursor>>,
+}
+
+impl DragPasteboard for ReplayPasteboard {
+    fn change_count(&self) -> i64 {
+        self.cursor.lock().unwrap().change_count
+    }
+
+    fn data(&self) -> Option<DragData> {
+        let change_count = self.change_count();
+        self.data_at(change_count)
+    }
+
+    // 按事件自身的变化计数取出快照，排队处理的事件不会读到之后的拖拽的数据
+    fn data_at(&self, change_count: i64) -> Option<DragData> {
+        let cursor = self.cursor.lock().unwrap();
+        cursor.snapshots.get(&change_count).cloned().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use super::*;
+    use crate::Dorazu;
+    use crate::clock::ManualClock;
+    use crate::event_queue::EventDelivery;
+    use crate::mock::{MockPasteboard, MockPointerBackend};
+
+    // 可以在录制结束后取出内容的共享缓冲区
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // 把实例上报的回调按顺序记为文字
+    fn callback_log(dorazu: &Dorazu) -> Arc<Mutex<Vec<String>>> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let text = |data: &Option<DragData>| match data {
+            Some(DragData::PlainText(text)) => text.clone(),
+            other => format!("{other:?}"),
+        };
+        {
+            let log = log.clone();
+            dorazu.subscribe_pasteboard_changed(Box::new(move |data| {
+                log.lock().unwrap().push(format!("changed {}", text(&data)));
+            }));
+        }
+        {
+            let log = log.clone();
+            dorazu.subscribe_shake(Box::new(move |event| {
+                let line = format!(
+                    "shake {} {:?} {}",
+                    event.ordinal,
+                    event.axis,
+                    text(&event.data)
+                );
+                log.lock().unwrap().push(line);
+            }));
+        }
+        {
+            let log = log.clone();
+            dorazu.subscribe_shake_ended(Box::new(move |event| {
+                log.lock()
+                    .unwrap()
+                    .push(format!("shake ended {}", event.ordinal));
+            }));
+        }
+        {
+            let log = log.clone();
+            dorazu.subscribe_drag_end(Box::new(move |data| {
+                log.lock().unwrap().push(format!("end {}", text(&data)));
+            }));
+        }
+        log
+    }
+
+    // 用手动时钟录制两次拖拽，事件都不带时间戳，返回轨迹和实时上报的回调
+    //
+    // 第一次拖拽以 250 毫秒的间隔慢慢来回移动，折返间隔超过 200 毫秒，不算摇动；
+    // 第二次拖拽写入新的数据，快速摇动后停下，之后才结束拖拽。
+    fn record_session() -> (Vec<u8>, Vec<String>) {
+        let clock = Arc::new(ManualClock::new());
+        let pasteboard = MockPasteboard::new();
+        let dorazu = Dorazu::builder()
+            .pasteboard(Box::new(pasteboard.clone()))
+            .event_delivery(EventDelivery::Synchronous)
+            .clock(clock.clone())
+            .build();
+        let log = callback_log(&dorazu);
+        let backend = MockPointerBackend::new();
+        let _listener = dorazu.start_listening_with(backend.clone()).unwrap();
+        let buffer = SharedBuffer::default();
+        dorazu.start_recording(buffer.clone()).unwrap();
+
+        let step = |x: f64, ms: u64| {
+            clock.advance(Duration::from_millis(ms));
+            backend.drag_to(x, 0.0);
+        };
+        let release = |x: f64| {
+            clock.advance(Duration::from_millis(20));
+            backend.release(x, 0.0);
+        };
+        backend.press(0.0, 0.0);
+        pasteboard.set_data(Some(DragData::PlainText("first".to_owned())));
+        for index in 0..8 {
+            step(if index % 2 == 0 { 50.0 } else { 0.0 }, 250);
+        }
+        release(0.0);
+
+        clock.advance(Duration::from_millis(100));
+        backend.press(0.0, 0.0);
+        pasteboard.set_data(Some(DragData::PlainText("second".to_owned())));
+        for index in 0..8 {
+            step(if index % 2 == 0 { 50.0 } else { 0.0 }, 20);
+        }
+        step(100.0, 300);
+        step(150.0, 20);
+        release(150.0);
+
+        dorazu.stop_recording().unwrap();
+        let trace = buffer.0.lock().unwrap().clone();
+        let log = log.lock().unwrap().clone();
+        (trace, log)
+    }
+
+    fn replay_session(trace: &[u8], speed: ReplaySpeed, delivery: EventDelivery) -> Vec<String> {
+        let backend = ReplayBackend::from_reader(Cursor::new(trace))
+            .unwrap()
+            .with_speed(speed);
+        // 回放的粘贴板在开始监听时由后端提供
+        let dorazu = Dorazu::builder().event_delivery(delivery).build();
+        let log = callback_log(&dorazu);
+        dorazu.start_listening_with(backend).unwrap().wait();
+        log.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_callbacks_at_every_speed() {
+        let (trace, live) = record_session();
+        assert_eq!(
+            live,
+            [
+                "changed first",
+                "end first",
+                "changed second",
+                "shake 1 Horizontal second",
+                "shake ended 1",
+                "end second",
+            ]
+        );
+
+        assert_eq!(
+            replay_session(&trace, ReplaySpeed::Original, EventDelivery::default()),
+            live
+        );
+        for speed in [ReplaySpeed::Scaled(10.0), ReplaySpeed::Immediate] {
+            for delivery in [EventDelivery::Synchronous, EventDelivery::default()] {
+                assert_eq!(
+                    replay_session(&trace, speed, delivery),
+                    live,
+                    "{speed:?} {delivery:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn recorded_timestamps_are_kept() {
+        let event = PointerEvent::Dragged {
+            button: crate::model::pointer_event::MouseButton::Left,
+            location: Point { x: 1.0, y: 2.0 },
+            timestamp: Some(Duration::from_millis(7)),
+        };
+        assert_eq!(with_recorded_timestamp(event, 500.0), event);
+
+        let PointerEvent::Dragged { timestamp, .. } = with_recorded_timestamp(
+            PointerEvent::Dragged {
+                button: crate::model::pointer_event::MouseButton::Left,
+                location: Point { x: 1.0, y: 2.0 },
+                timestamp: None,
+            },
+            1500.0,
+        ) else {
+            unreachable!();
+        };
+        assert_eq!(timestamp, Some(Duration::from_millis(1500)));
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+/// 摇动检测的阈值配置
+///
+/// 不同的输入设备移动特性差别很大，可以从预设开始按需调整各字段。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShakeConfig {
+    /// 保留鼠标位置历史的时长
+    pub history_window: Duration,
+    /// 相邻两次方向变化的最长间隔，超过后重新计数
+    pub change_interval: Duration,
+    /// 判定为摇动所需的连续方向变化次数
+    pub min_direction_changes: usize,
+    /// 两次折返之间至少移动的距离（像素），行程不足的折返不计数
+    pub min_travel: f64,
+    /// 死区大小（像素），回退不超过该距离的移动视为抖动，不算作折返
+    pub dead_zone: f64,
+    /// 重复摇动的冷却时间，为 `None` 时每次拖拽只上报一次摇动
+    ///
+    /// 设置后，上一次摇动结束并经过该时长后再次摇动会再次上报。
+    pub repeat_cooldown: Option<Duration>,
+}
+
+impl ShakeConfig {
+    /// 默认配置，适合普通鼠标
+    pub const DEFAULT: Self = Self {
+        history_window: Duration::from_millis(500),
+        change_interval: Duration::from_millis(200),
+        min_direction_changes: 4,
+        min_travel: 10.0,
+        dead_zone: 2.0,
+        repeat_cooldown: None,
+    };
+
+    /// 触控板预设，手指来回移动较慢，放宽间隔并减少所需次数
+    pub const TRACKPAD: Self = Self {
+        history_window: Duration::from_millis(800),
+        change_interval: Duration::from_millis(300),
+        min_direction_changes: 3,
+        min_travel: 8.0,
+        dead_zone: 1.0,
+        repeat_cooldown: None,
+    };
+
+    /// 高回报率游戏鼠标预设，缩短间隔并要求更多次方向变化，避免快速甩动被误判
+    pub const GAMING_MOUSE: Self = Self {
+        history_window: Duration::from_millis(400),
+        change_interval: Duration::from_millis(150),
+        min_direction_changes: 5,
+        min_travel: 15.0,
+        dead_zone: 3.0,
+        repeat_cooldown: None,
+    };
+}
+
+impl Default for ShakeConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
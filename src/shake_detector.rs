@@ -1,47 +1,134 @@
-use std::time::{Instant, Duration};
+use std::time::Duration;
 use std::collections::VecDeque;
 
+use crate::model::shake_event::{ShakeAxis, ShakeInfo};
+use crate::shake_config::ShakeConfig;
+
 // 鼠标位置记录，包含位置和时间戳
-pub struct MousePosition {
+pub(crate) struct MousePosition {
     pub x: f64,
     pub y: f64,
-    pub timestamp: Instant,
+    pub timestamp: Duration,
+}
+
+// 单个坐标轴上的移动方向跟踪
+struct AxisTracker {
+    // 当前移动方向 (true为正方向)，尚未移出死区时为空
+    direction: Option<bool>,
+    // 当前这段移动的起点，即上一次折返的位置
+    anchor: f64,
+    // 当前方向上到达的最远位置
+    extreme: f64,
 }
 
+impl AxisTracker {
+    // 从指定位置开始跟踪
+    fn new(value: f64) -> Self {
+        Self {
+            direction: None,
+            anchor: value,
+            extreme: value,
+        }
+    }
+
+    // 更新位置，发生有效的折返时返回折返前这段移动的距离
+    //
+    // 回退距离不超过死区的移动视为抖动，折返前的这段移动不足最小行程时不计数。
+    fn update(&mut self, value: f64, dead_zone: f64, min_travel: f64) -> Option<f64> {
+        let Some(positive) = self.direction else {
+            if (value - self.anchor).abs() > dead_zone {
+                self.direction = Some(value > self.anchor);
+                self.extreme = value;
+            }
+            return None;
+        };
+
+        let (advance, retreat) = if positive {
+            (value - self.extreme, self.extreme - value)
+        } else {
+            (self.extreme - value, value - self.extreme)
+        };
+        if advance >= 0.0 {
+            self.extreme = value;
+            return None;
+        }
+        if retreat <= dead_zone {
+            return None;
+        }
+
+        let travel = (self.extreme - self.anchor).abs();
+        self.direction = Some(!positive);
+        self.anchor = self.extreme;
+        self.extreme = value;
+        (travel >= min_travel).then_some(travel)
+    }
+}
+
+/// 根据方向折返次数识别鼠标摇动的手势识别器
+///
+/// 除了内置的摇动检测，也可以用不同的阈值创建并注册为额外的识别器。
 pub struct ShakeDetector {
+    // 检测阈值
+    config: ShakeConfig,
     // 存储最近的鼠标位置
     position_history: VecDeque<MousePosition>,
     // 上一次方向变化的时间
-    last_direction_change: Option<Instant>,
+    last_direction_change: Option<Duration>,
     // 方向变化计数
     direction_changes: usize,
-    // 上一次检测到的方向 (x方向: true为右, false为左)
-    last_x_direction: Option<bool>,
-    // 上一次检测到的方向 (y方向: true为下, false为上)
-    last_y_direction: Option<bool>,
+    // 本轮计数中x方向和y方向各自的折返次数，用于判断摇动方向
+    horizontal_changes: usize,
+    vertical_changes: usize,
+    // 本轮计数中每次折返前移动的距离
+    reversal_amplitudes: Vec<f64>,
+    // 本轮计数中第一次折返的时间
+    run_started: Option<Duration>,
+    // x方向的移动跟踪 (正方向为右)，收到第一个位置后开始
+    x_axis: Option<AxisTracker>,
+    // y方向的移动跟踪 (正方向为下)，收到第一个位置后开始
+    y_axis: Option<AxisTracker>,
     // 是否处于摇动状态
     is_shaking: bool,
-    // 是否已经在当前拖拽中检测到摇动并处理
-    shake_detected_in_current_drag: bool,
+    // 当前拖拽中已经上报的摇动次数
+    shakes_in_current_drag: usize,
+    // 已上报的摇动是否还在持续
+    shake_active: bool,
+    // 上一次摇动结束的时间，用于计算冷却时间
+    last_shake_ended: Option<Duration>,
 }
 
 impl ShakeDetector {
-    pub fn new() -> Self {
+    pub fn new(config: ShakeConfig) -> Self {
         Self {
+            config,
             position_history: VecDeque::with_capacity(10),
             last_direction_change: None,
             direction_changes: 0,
-            last_x_direction: None,
-            last_y_direction: None,
+            horizontal_changes: 0,
+            vertical_changes: 0,
+            reversal_amplitudes: Vec::new(),
+            run_started: None,
+            x_axis: None,
+            y_axis: None,
             is_shaking: false,
-            shake_detected_in_current_drag: false,
+            shakes_in_current_drag: 0,
+            shake_active: false,
+            last_shake_ended: None,
         }
     }
     
+    // 获取检测阈值
+    pub fn config(&self) -> ShakeConfig {
+        self.config
+    }
+    
+    // 替换检测阈值，已记录的位置和计数保持不变
+    pub fn set_config(&mut self, config: ShakeConfig) {
+        self.config = config;
+    }
+    
     // 添加鼠标位置到历史记录
-    pub fn add_position(&mut self, x: f64, y: f64) {
-        let now = Instant::now();
-        
+    pub(crate) fn add_position(&mut self, x: f64, y: f64, now: Duration) {
         // 添加新位置
         self.position_history.push_back(MousePosition {
             x,
@@ -49,9 +136,9 @@ impl ShakeDetector {
             timestamp: now,
         });
         
-        // 移除过旧的记录，只保留最近500ms内的记录
+        // 移除过旧的记录，只保留历史窗口内的记录
         while let Some(pos) = self.position_history.front() {
-            if now.duration_since(pos.timestamp) > Duration::from_millis(500) {
+            if now.saturating_sub(pos.timestamp) > self.config.history_window {
                 self.position_history.pop_front();
             } else {
                 break;
@@ -61,88 +148,302 @@ impl ShakeDetector {
         // 检测方向变化
         self.detect_direction_change();
         
+        // 超过间隔没有新的折返时，本轮计数结束
+        if let Some(last_change) = self.last_direction_change
+            && now.saturating_sub(last_change) >= self.config.change_interval
+        {
+            self.direction_changes = 0;
+        }
+        
         // 检测是否为摇动
         self.detect_shake();
     }
     
     // 检测方向变化
     fn detect_direction_change(&mut self) {
-        if self.position_history.len() < 3 {
+        let Some(current) = self.position_history.back() else {
             return;
-        }
+        };
+        let (x, y, now) = (current.x, current.y, current.timestamp);
         
-        // 获取最近的两个位置
-        let positions: Vec<&MousePosition> = self.position_history.iter().rev().take(3).collect();
-        let current = positions[0];
-        let previous = positions[1];
-        let before_previous = positions[2];
-        
-        // 计算当前移动方向
-        let current_x_direction = current.x > previous.x;
-        let current_y_direction = current.y > previous.y;
+        // 第一个位置作为两个方向的起点
+        let (Some(x_axis), Some(y_axis)) = (&mut self.x_axis, &mut self.y_axis) else {
+            self.x_axis = Some(AxisTracker::new(x));
+            self.y_axis = Some(AxisTracker::new(y));
+            return;
+        };
         
-        // 计算前一次移动方向
-        let previous_x_direction = previous.x > before_previous.x;
-        let previous_y_direction = previous.y > before_previous.y;
+        let dead_zone = self.config.dead_zone;
+        let min_travel = self.config.min_travel;
         
-        // 检测X或Y方向是否发生变化
-        let x_direction_changed = current_x_direction != previous_x_direction;
-        let y_direction_changed = current_y_direction != previous_y_direction;
+        // 检测X或Y方向是否发生折返
+        let x_travel = x_axis.update(x, dead_zone, min_travel);
+        let y_travel = y_axis.update(y, dead_zone, min_travel);
+        let x_direction_changed = x_travel.is_some();
+        let y_direction_changed = y_travel.is_some();
         
         // 如果方向发生变化
         if x_direction_changed || y_direction_changed {
-            let now = Instant::now();
-            
+            // 第一次折返开始新一轮计数
+            if self.last_direction_change.is_none() {
+                self.start_run(now);
+            }
+
             // 更新方向变化时间
             if let Some(last_change) = self.last_direction_change {
-                // 如果方向变化间隔小于200ms，增加方向变化计数
-                if now.duration_since(last_change) < Duration::from_millis(200) {
+                // 如果方向变化间隔小于配置的间隔，增加方向变化计数
+                if now.saturating_sub(last_change) < self.config.change_interval {
                     self.direction_changes += 1;
                 } else {
                     // 重置计数
                     self.direction_changes = 1;
+                    self.start_run(now);
                 }
             }
             
+            self.horizontal_changes += usize::from(x_direction_changed);
+            self.vertical_changes += usize::from(y_direction_changed);
+            // 两个方向同时折返时取合成的距离
+            let amplitude = x_travel.unwrap_or(0.0).hypot(y_travel.unwrap_or(0.0));
+            self.reversal_amplitudes.push(amplitude);
             self.last_direction_change = Some(now);
-            self.last_x_direction = Some(current_x_direction);
-            self.last_y_direction = Some(current_y_direction);
         }
     }
     
+    // 开始新一轮方向变化计数
+    fn start_run(&mut self, now: Duration) {
+        self.horizontal_changes = 0;
+        self.vertical_changes = 0;
+        self.reversal_amplitudes.clear();
+        self.run_started = Some(now);
+    }
+    
     // 检测是否为摇动
     fn detect_shake(&mut self) {
         // 如果在短时间内方向变化次数超过阈值，判定为摇动
-        if self.direction_changes >= 4 {
-            self.is_shaking = true;
+        self.is_shaking = self.direction_changes >= self.config.min_direction_changes;
+    }
+    
+    // 根据两个方向的折返次数判断摇动方向，次数相差不到一倍时视为斜向
+    pub(crate) fn shake_axis(&self) -> ShakeAxis {
+        let (horizontal, vertical) = (self.horizontal_changes, self.vertical_changes);
+        if horizontal > 0 && vertical > 0 && horizontal.min(vertical) * 2 >= horizontal.max(vertical) {
+            ShakeAxis::Diagonal
+        } else if horizontal >= vertical {
+            ShakeAxis::Horizontal
+        } else {
+            ShakeAxis::Vertical
+        }
+    }
+    
+    // 根据本轮计数的折返和位置历史汇总摇动的强度
+    //
+    // 置信度取三项的平均值：折返次数达到阈值两倍的程度、平均幅度达到最小行程四倍的程度，
+    // 以及各次幅度的一致程度。
+    pub(crate) fn shake_info(&self) -> ShakeInfo {
+        let amplitudes = &self.reversal_amplitudes;
+        let reversal_count = amplitudes.len();
+        let average_amplitude = if reversal_count == 0 {
+            0.0
+        } else {
+            amplitudes.iter().sum::<f64>() / reversal_count as f64
+        };
+        
+        let peak_velocity = self
+            .position_history
+            .iter()
+            .zip(self.position_history.iter().skip(1))
+            .filter_map(|(previous, current)| {
+                let elapsed = current.timestamp.saturating_sub(previous.timestamp).as_secs_f64();
+                let distance = (current.x - previous.x).hypot(current.y - previous.y);
+                (elapsed > 0.0).then(|| distance / elapsed)
+            })
+            .fold(0.0, f64::max);
+        
+        let duration = match (self.run_started, self.position_history.back()) {
+            (Some(started), Some(last)) => last.timestamp.saturating_sub(started),
+            _ => Duration::ZERO,
+        };
+        
+        let count_score =
+            (reversal_count as f64 / (2 * self.config.min_direction_changes.max(1)) as f64).min(1.0);
+        let amplitude_score = if self.config.min_travel > 0.0 {
+            (average_amplitude / (4.0 * self.config.min_travel)).min(1.0)
         } else {
-            self.is_shaking = false;
+            1.0
+        };
+        let regularity_score = if average_amplitude > 0.0 {
+            let variance = amplitudes
+                .iter()
+                .map(|amplitude| (amplitude - average_amplitude).powi(2))
+                .sum::<f64>()
+                / reversal_count as f64;
+            1.0 / (1.0 + variance.sqrt() / average_amplitude)
+        } else {
+            0.0
+        };
+        
+        ShakeInfo {
+            reversal_count,
+            average_amplitude,
+            peak_velocity,
+            duration,
+            confidence: (count_score + amplitude_score + regularity_score) / 3.0,
         }
     }
     
     // 重置摇动检测状态
-    pub fn reset(&mut self) {
+    pub(crate) fn reset(&mut self) {
         self.direction_changes = 0;
+        self.horizontal_changes = 0;
+        self.vertical_changes = 0;
+        self.reversal_amplitudes.clear();
+        self.run_started = None;
         self.is_shaking = false;
         self.position_history.clear();
         self.last_direction_change = None;
-        self.last_x_direction = None;
-        self.last_y_direction = None;
-        self.shake_detected_in_current_drag = false;
+        self.x_axis = None;
+        self.y_axis = None;
+        self.shakes_in_current_drag = 0;
+        self.shake_active = false;
+        self.last_shake_ended = None;
     }
     
-    // 获取当前是否处于摇动状态
-    pub fn is_shaking(&self) -> bool {
-        self.is_shaking
+    // 是否应该上报一次新的摇动
+    //
+    // 每次拖拽默认只上报一次；配置了重复摇动的冷却时间时，
+    // 上一次摇动结束并经过冷却时间后可以再次上报。
+    pub(crate) fn should_report_shake(&self) -> bool {
+        if !self.is_shaking || self.shake_active {
+            return false;
+        }
+        if self.shakes_in_current_drag == 0 {
+            return true;
+        }
+        match (self.config.repeat_cooldown, self.last_shake_ended, self.position_history.back()) {
+            (Some(cooldown), Some(ended), Some(current)) => {
+                current.timestamp.saturating_sub(ended) >= cooldown
+            }
+            _ => false,
+        }
     }
     
-    // 获取当前是否已在当前拖拽中检测到摇动
-    pub fn is_shake_detected_in_current_drag(&self) -> bool {
-        self.shake_detected_in_current_drag
+    // 记录开始上报一次摇动，返回它在当前拖拽中的序号（从 1 开始）
+    pub(crate) fn begin_shake(&mut self) -> usize {
+        self.shakes_in_current_drag += 1;
+        self.shake_active = true;
+        self.shakes_in_current_drag
     }
     
-    // 设置已在当前拖拽中检测到摇动
-    pub fn set_shake_detected_in_current_drag(&mut self, value: bool) {
-        self.shake_detected_in_current_drag = value;
+    // 已上报的摇动是否已经停止
+    pub(crate) fn has_shake_ended(&self) -> bool {
+        self.shake_active && !self.is_shaking
+    }
+    
+    // 记录摇动结束，返回结束的摇动的序号
+    pub(crate) fn end_shake(&mut self) -> usize {
+        self.shake_active = false;
+        self.last_shake_ended = self.position_history.back().map(|position| position.timestamp);
+        self.shakes_in_current_drag
+    }
+    
+    // 已上报的摇动是否还在持续
+    pub(crate) fn is_shake_active(&self) -> bool {
+        self.shake_active
+    }
+    
+    // 当前拖拽中已经上报的摇动次数，即最近一次摇动的序号
+    pub(crate) fn shake_count(&self) -> usize {
+        self.shakes_in_current_drag
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 依次输入 x 坐标和毫秒时间戳，y 坐标保持不变
+    fn feed(detector: &mut ShakeDetector, positions: &[(f64, u64)]) {
+        for &(x, ms) in positions {
+            detector.add_position(x, 0.0, Duration::from_millis(ms));
+        }
     }
-}
\ No newline at end of file
+
+    // 从 `start_ms` 开始每隔 `step_ms` 在两个位置之间来回移动
+    fn back_and_forth(
+        a: f64,
+        b: f64,
+        count: usize,
+        start_ms: u64,
+        step_ms: u64,
+    ) -> Vec<(f64, u64)> {
+        (0..count)
+            .map(|i| (if i % 2 == 0 { a } else { b }, start_ms + i as u64 * step_ms))
+            .collect()
+    }
+
+    #[test]
+    fn fast_reversals_are_reported_as_horizontal_shake() {
+        let mut detector = ShakeDetector::new(ShakeConfig::DEFAULT);
+        feed(&mut detector, &back_and_forth(0.0, 50.0, 8, 0, 20));
+
+        assert!(detector.should_report_shake());
+        assert_eq!(detector.shake_axis(), ShakeAxis::Horizontal);
+        assert_eq!(detector.begin_shake(), 1);
+        assert!(!detector.should_report_shake());
+    }
+
+    #[test]
+    fn jitter_inside_dead_zone_is_not_a_reversal() {
+        let mut detector = ShakeDetector::new(ShakeConfig::DEFAULT);
+        // 先向右移出死区，之后只在不超过死区的范围内来回抖动
+        feed(&mut detector, &[(0.0, 0), (50.0, 20)]);
+        feed(&mut detector, &back_and_forth(48.5, 50.0, 20, 40, 10));
+
+        assert!(!detector.should_report_shake());
+        assert_eq!(detector.shake_info().reversal_count, 0);
+    }
+
+    #[test]
+    fn reversals_shorter_than_min_travel_are_ignored() {
+        let mut detector = ShakeDetector::new(ShakeConfig::DEFAULT);
+        // 每段移动超出死区但不足 10 像素的最小行程
+        feed(&mut detector, &back_and_forth(0.0, 8.0, 20, 0, 20));
+
+        assert!(!detector.should_report_shake());
+        assert_eq!(detector.shake_info().reversal_count, 0);
+    }
+
+    #[test]
+    fn pause_longer_than_change_interval_restarts_count() {
+        let config = ShakeConfig::DEFAULT;
+        let mut detector = ShakeDetector::new(config);
+        // 每轮的第一次折返只开始计数，这里累计的方向变化比判定所需少一次
+        let burst = config.min_direction_changes + 2;
+        feed(&mut detector, &back_and_forth(0.0, 50.0, burst, 0, 20));
+        assert!(!detector.should_report_shake());
+
+        // 停顿超过 200 毫秒后再折返一次，计数从头开始而不是达到阈值
+        let resumed = 20 * (burst as u64 - 1) + config.change_interval.as_millis() as u64;
+        feed(&mut detector, &[(0.0, resumed)]);
+        assert!(!detector.should_report_shake());
+
+        // 在间隔内继续折返，重新累计到阈值后判定为摇动
+        let remaining = config.min_direction_changes - 1;
+        feed(&mut detector, &back_and_forth(50.0, 0.0, remaining, resumed + 20, 20));
+        assert!(detector.should_report_shake());
+    }
+
+    #[test]
+    fn shake_ends_after_change_interval_without_reversal() {
+        let mut detector = ShakeDetector::new(ShakeConfig::DEFAULT);
+        feed(&mut detector, &back_and_forth(0.0, 50.0, 8, 0, 20));
+        detector.begin_shake();
+        assert!(!detector.has_shake_ended());
+
+        // 沿同一方向移动超过 200 毫秒
+        feed(&mut detector, &[(100.0, 300), (150.0, 400)]);
+        assert!(detector.has_shake_ended());
+        assert_eq!(detector.end_shake(), 1);
+        assert!(!detector.is_shake_active());
+    }
+}
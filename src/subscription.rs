@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 订阅标识，用于取消订阅
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+impl SubscriptionId {
+    // 生成全局唯一的订阅标识
+    fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+// 一类事件的订阅者列表
+pub(crate) struct Subscribers<T: ?Sized> {
+    entries: Vec<(SubscriptionId, Arc<T>)>,
+    // 通过 set_*_callback 设置的订阅，再次设置时被替换
+    set_id: Option<SubscriptionId>,
+}
+
+impl<T: ?Sized> Default for Subscribers<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            set_id: None,
+        }
+    }
+}
+
+impl<T: ?Sized> Subscribers<T> {
+    // 添加订阅者
+    pub(crate) fn subscribe(&mut self, subscriber: Arc<T>) -> SubscriptionId {
+        let id = SubscriptionId::next();
+        self.entries.push((id, subscriber));
+        id
+    }
+
+    // 替换上一次通过 set 设置的订阅者
+    pub(crate) fn set(&mut self, subscriber: Arc<T>) -> SubscriptionId {
+        if let Some(previous) = self.set_id.take() {
+            self.unsubscribe(previous);
+        }
+        let id = self.subscribe(subscriber);
+        self.set_id = Some(id);
+        id
+    }
+
+    // 移除订阅者，返回是否存在
+    pub(crate) fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|(entry_id, _)| *entry_id != id);
+        self.entries.len() != len
+    }
+
+    // 复制当前订阅者列表，调用时不持有锁，订阅者可以在回调中增删订阅
+    pub(crate) fn snapshot(&self) -> Vec<Arc<T>> {
+        self.entries
+            .iter()
+            .map(|(_, subscriber)| subscriber.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,523 @@
+use std::f64::consts::SQRT_2;
+#[cfg(feature = "templates")]
+use std::fs::File;
+#[cfg(feature = "templates")]
+use std::io::{self, BufReader, Read};
+#[cfg(feature = "templates")]
+use std::path::Path;
+use std::time::Duration;
+
+use crate::gesture::GestureRecognizer;
+use crate::model::gesture_event::GestureDetail;
+use crate::model::pointer_event::Point;
+
+// 宽高之比低于该值的路径视为一维的（如直线），按比例缩放而不是拉伸成正方形
+const ONE_D_THRESHOLD: f64 = 0.3;
+
+/// 模板匹配的参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemplateConfig {
+    /// 路径和模板统一重采样的点数
+    pub resample_count: usize,
+    /// 比较前把路径缩放到的正方形边长（像素）
+    pub square_size: f64,
+    /// 是否忽略路径的整体朝向，开启时旋转后的路径也能匹配同一个模板
+    pub rotation_invariant: bool,
+    /// 忽略朝向时在起始角度两侧搜索的范围（弧度）
+    pub angle_range: f64,
+    /// 搜索最佳角度的精度（弧度）
+    pub angle_precision: f64,
+    /// 上报匹配所需的最低分数，分数在 0 到 1 之间，越大越相似
+    pub min_score: f64,
+    /// 路径的最小长度（像素），更短的拖拽不参与匹配
+    pub min_path_length: f64,
+}
+
+impl Default for TemplateConfig {
+    fn default() -> Self {
+        Self {
+            resample_count: 64,
+            square_size: 250.0,
+            rotation_invariant: true,
+            angle_range: 45f64.to_radians(),
+            angle_precision: 2f64.to_radians(),
+            min_score: 0.8,
+            min_path_length: 50.0,
+        }
+    }
+}
+
+/// 一个命名的单笔手势模板
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "templates", derive(serde::Serialize, serde::Deserialize))]
+pub struct GestureTemplate {
+    /// 模板名称，匹配时作为结果的名称
+    pub name: String,
+    /// 按绘制顺序排列的路径点，坐标的位置和大小不影响匹配
+    pub points: Vec<Point>,
+}
+
+// 模板及其归一化后的路径
+struct PreparedTemplate {
+    template: GestureTemplate,
+    normalized: Vec<Point>,
+}
+
+/// 把拖拽路径与命名模板比较的单笔手势识别器（$1 Unistroke Recognizer）
+///
+/// 拖拽过程中只记录路径，拖拽结束时把路径重采样、旋转、缩放并平移后与各个模板逐点比较，
+/// 分数最高且不低于阈值的模板作为结果上报。启用 `templates` 特性后可以从 JSON 文件读取模板。
+pub struct TemplateRecognizer {
+    // 匹配参数
+    config: TemplateConfig,
+    // 已添加的模板
+    templates: Vec<PreparedTemplate>,
+    // 当前拖拽的路径
+    path: Vec<Point>,
+}
+
+impl TemplateRecognizer {
+    pub fn new(config: TemplateConfig) -> Self {
+        Self {
+            config,
+            templates: Vec::new(),
+            path: Vec::new(),
+        }
+    }
+
+    // 获取匹配参数
+    pub fn config(&self) -> TemplateConfig {
+        self.config
+    }
+
+    // 替换匹配参数，已添加的模板按新参数重新归一化
+    pub fn set_config(&mut self, config: TemplateConfig) {
+        self.config = config;
+        for prepared in &mut self.templates {
+            // 能否归一化只取决于路径本身，添加时已经检查过
+            if let Some(normalized) = normalize(&prepared.template.points, &config) {
+                prepared.normalized = normalized;
+            }
+        }
+    }
+
+    /// 添加模板，替换同名的模板
+    ///
+    /// 点数少于两个或长度为零的路径无法匹配，此时不添加并返回 `false`。
+    pub fn add_template(&mut self, template: GestureTemplate) -> bool {
+        let Some(normalized) = normalize(&template.points, &self.config) else {
+            return false;
+        };
+        self.templates.retain(|prepared| prepared.template.name != template.name);
+        self.templates.push(PreparedTemplate {
+            template,
+            normalized,
+        });
+        true
+    }
+
+    /// 移除模板，返回是否存在该名称的模板
+    pub fn remove_template(&mut self, name: &str) -> bool {
+        let count = self.templates.len();
+        self.templates.retain(|prepared| prepared.template.name != name);
+        self.templates.len() != count
+    }
+
+    /// 已添加的模板
+    pub fn templates(&self) -> impl Iterator<Item = &GestureTemplate> {
+        self.templates.iter().map(|prepared| &prepared.template)
+    }
+
+    /// 读取 JSON 格式的模板数组并全部添加，返回添加的模板数
+    ///
+    /// 格式为 `[{"name": "z", "points": [{"x": 0, "y": 0}, ...]}, ...]`。
+    /// 其中有无法匹配的模板时返回错误，不添加任何模板。
+    #[cfg(feature = "templates")]
+    pub fn read_templates(&mut self, reader: impl Read) -> io::Result<usize> {
+        let templates: Vec<GestureTemplate> = serde_json::from_reader(reader)?;
+        if let Some(template) = templates
+            .iter()
+            .find(|template| normalize(&template.points, &self.config).is_none())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("template {:?} needs at least two distinct points", template.name),
+            ));
+        }
+        let count = templates.len();
+        for template in templates {
+            self.add_template(template);
+        }
+        Ok(count)
+    }
+
+    /// 打开模板文件并添加其中的全部模板
+    #[cfg(feature = "templates")]
+    pub fn load_templates(&mut self, path: impl AsRef<Path>) -> io::Result<usize> {
+        self.read_templates(BufReader::new(File::open(path)?))
+    }
+
+    // 找到与路径最相似的模板，返回其名称和分数
+    fn best_match(&self, path: &[Point]) -> Option<(&str, f64)> {
+        let candidate = normalize(path, &self.config)?;
+        let half_diagonal = 0.5 * SQRT_2 * self.config.square_size;
+        self.templates
+            .iter()
+            .map(|prepared| {
+                let distance = if self.config.rotation_invariant {
+                    distance_at_best_angle(&candidate, &prepared.normalized, &self.config)
+                } else {
+                    path_distance(&candidate, &prepared.normalized)
+                };
+                (prepared.template.name.as_str(), 1.0 - distance / half_diagonal)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+impl GestureRecognizer for TemplateRecognizer {
+    fn reset(&mut self) {
+        self.path.clear();
+    }
+
+    fn add_position(&mut self, location: Point, _timestamp: Duration) -> Option<GestureDetail> {
+        self.path.push(location);
+        None
+    }
+
+    fn finish(&mut self) -> Option<GestureDetail> {
+        let path = std::mem::take(&mut self.path);
+        if path_length(&path) < self.config.min_path_length {
+            return None;
+        }
+        let (name, score) = self.best_match(&path)?;
+        (score >= self.config.min_score).then(|| GestureDetail::Template {
+            name: name.to_owned(),
+            score,
+        })
+    }
+}
+
+// 重采样、旋转、缩放并平移到原点，路径无法归一化时返回 `None`
+fn normalize(points: &[Point], config: &TemplateConfig) -> Option<Vec<Point>> {
+    if points.len() < 2 || path_length(points) <= 0.0 {
+        return None;
+    }
+    let mut points = resample(points, config.resample_count.max(2));
+    if config.rotation_invariant {
+        let angle = indicative_angle(&points);
+        points = rotate_by(&points, -angle);
+    }
+    points = scale_to(&points, config.square_size);
+    Some(translate_to_origin(&points))
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+fn path_length(points: &[Point]) -> f64 {
+    points.windows(2).map(|pair| distance(pair[0], pair[1])).sum()
+}
+
+// 沿路径等距取 `count` 个点
+fn resample(points: &[Point], count: usize) -> Vec<Point> {
+    let interval = path_length(points) / (count - 1) as f64;
+    let mut resampled = vec![points[0]];
+    let mut previous = points[0];
+    let mut accumulated = 0.0;
+    let mut index = 1;
+    while index < points.len() {
+        let current = points[index];
+        let step = distance(previous, current);
+        if step > 0.0 && accumulated + step >= interval {
+            let t = (interval - accumulated) / step;
+            let point = Point {
+                x: previous.x + t * (current.x - previous.x),
+                y: previous.y + t * (current.y - previous.y),
+            };
+            resampled.push(point);
+            // 新插入的点作为下一段的起点
+            previous = point;
+            accumulated = 0.0;
+        } else {
+            accumulated += step;
+            previous = current;
+            index += 1;
+        }
+    }
+    // 浮点误差可能导致少一个点
+    resampled.truncate(count);
+    while resampled.len() < count {
+        resampled.push(points[points.len() - 1]);
+    }
+    resampled
+}
+
+fn centroid(points: &[Point]) -> Point {
+    let count = points.len() as f64;
+    let (x, y) = points
+        .iter()
+        .fold((0.0, 0.0), |(x, y), point| (x + point.x, y + point.y));
+    Point {
+        x: x / count,
+        y: y / count,
+    }
+}
+
+// 起点相对重心的角度
+fn indicative_angle(points: &[Point]) -> f64 {
+    let center = centroid(points);
+    (center.y - points[0].y).atan2(center.x - points[0].x)
+}
+
+// 绕重心旋转
+fn rotate_by(points: &[Point], angle: f64) -> Vec<Point> {
+    let center = centroid(points);
+    let (sin, cos) = angle.sin_cos();
+    points
+        .iter()
+        .map(|point| {
+            let (dx, dy) = (point.x - center.x, point.y - center.y);
+            Point {
+                x: dx * cos - dy * sin + center.x,
+                y: dx * sin + dy * cos + center.y,
+            }
+        })
+        .collect()
+}
+
+// 缩放到边长为 `size` 的正方形，接近一维的路径保持宽高比
+fn scale_to(points: &[Point], size: f64) -> Vec<Point> {
+    let (min_x, max_x, min_y, max_y) = points.iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY),
+        |(min_x, max_x, min_y, max_y), point| {
+            (min_x.min(point.x), max_x.max(point.x), min_y.min(point.y), max_y.max(point.y))
+        },
+    );
+    let (width, height) = (max_x - min_x, max_y - min_y);
+    let (scale_x, scale_y) = if width.min(height) / width.max(height) < ONE_D_THRESHOLD {
+        let scale = size / width.max(height);
+        (scale, scale)
+    } else {
+        (size / width, size / height)
+    };
+    points
+        .iter()
+        .map(|point| Point {
+            x: point.x * scale_x,
+            y: point.y * scale_y,
+        })
+        .collect()
+}
+
+// 平移使重心位于原点
+fn translate_to_origin(points: &[Point]) -> Vec<Point> {
+    let center = centroid(points);
+    points
+        .iter()
+        .map(|point| Point {
+            x: point.x - center.x,
+            y: point.y - center.y,
+        })
+        .collect()
+}
+
+// 对应点之间的平均距离
+fn path_distance(a: &[Point], b: &[Point]) -> f64 {
+    let count = a.len().min(b.len()).max(1) as f64;
+    a.iter().zip(b).map(|(a, b)| distance(*a, *b)).sum::<f64>() / count
+}
+
+// 在角度范围内用黄金分割搜索使路径与模板距离最小的旋转角度
+fn distance_at_best_angle(points: &[Point], template: &[Point], config: &TemplateConfig) -> f64 {
+    let phi = 0.5 * (5f64.sqrt() - 1.0);
+    let distance_at = |angle: f64| path_distance(&rotate_by(points, angle), template);
+    let (mut a, mut b) = (-config.angle_range, config.angle_range);
+    let mut x1 = phi * a + (1.0 - phi) * b;
+    let mut f1 = distance_at(x1);
+    let mut x2 = (1.0 - phi) * a + phi * b;
+    let mut f2 = distance_at(x2);
+    while (b - a).abs() > config.angle_precision.max(f64::EPSILON) {
+        if f1 < f2 {
+            b = x2;
+            x2 = x1;
+            f2 = f1;
+            x1 = phi * a + (1.0 - phi) * b;
+            f1 = distance_at(x1);
+        } else {
+            a = x1;
+            x1 = x2;
+            f1 = f2;
+            x2 = (1.0 - phi) * a + phi * b;
+            f2 = distance_at(x2);
+        }
+    }
+    f1.min(f2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coordinates: &[(f64, f64)]) -> Vec<Point> {
+        coordinates.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    fn template(name: &str, coordinates: &[(f64, f64)]) -> GestureTemplate {
+        GestureTemplate {
+            name: name.to_owned(),
+            points: points(coordinates),
+        }
+    }
+
+    fn recognizer() -> TemplateRecognizer {
+        let mut recognizer = TemplateRecognizer::new(TemplateConfig::default());
+        assert!(recognizer.add_template(template(
+            "z",
+            &[(0.0, 0.0), (100.0, 0.0), (0.0, 100.0), (100.0, 100.0)],
+        )));
+        assert!(recognizer.add_template(template(
+            "check",
+            &[(0.0, 50.0), (30.0, 100.0), (100.0, 0.0)],
+        )));
+        assert!(recognizer.add_template(template(
+            "caret",
+            &[(0.0, 100.0), (50.0, 0.0), (100.0, 100.0)],
+        )));
+        recognizer
+    }
+
+    // 把路径按拖拽的方式逐点输入，拖拽结束时取出结果
+    fn recognize(recognizer: &mut TemplateRecognizer, path: &[Point]) -> Option<GestureDetail> {
+        recognizer.reset();
+        for (index, &location) in path.iter().enumerate() {
+            let timestamp = Duration::from_millis(index as u64 * 10);
+            assert!(recognizer.add_position(location, timestamp).is_none());
+        }
+        recognizer.finish()
+    }
+
+    // 逐段插值得到较密的路径，并整体缩放、平移和旋转
+    fn drawn(coordinates: &[(f64, f64)], scale: f64, offset: (f64, f64), angle: f64) -> Vec<Point> {
+        let (sin, cos) = angle.sin_cos();
+        coordinates
+            .windows(2)
+            .flat_map(|pair| {
+                let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                (0..10).map(move |step| {
+                    let t = step as f64 / 10.0;
+                    (x0 + t * (x1 - x0), y0 + t * (y1 - y0))
+                })
+            })
+            .chain(coordinates.last().copied())
+            .map(|(x, y)| Point {
+                x: (x * cos - y * sin) * scale + offset.0,
+                y: (x * sin + y * cos) * scale + offset.1,
+            })
+            .collect()
+    }
+
+    fn matched_name(detail: Option<GestureDetail>) -> Option<String> {
+        match detail {
+            Some(GestureDetail::Template { name, score }) => {
+                assert!((0.8..=1.0).contains(&score), "score {score}");
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn transformed_paths_match_their_templates() {
+        let mut recognizer = recognizer();
+        let cases: [(&str, &[(f64, f64)]); 3] = [
+            ("z", &[(0.0, 0.0), (100.0, 0.0), (0.0, 100.0), (100.0, 100.0)]),
+            ("check", &[(0.0, 50.0), (30.0, 100.0), (100.0, 0.0)]),
+            ("caret", &[(0.0, 100.0), (50.0, 0.0), (100.0, 100.0)]),
+        ];
+        for (name, coordinates) in cases {
+            let path = drawn(coordinates, 2.5, (300.0, -40.0), 10f64.to_radians());
+            assert_eq!(
+                matched_name(recognize(&mut recognizer, &path)).as_deref(),
+                Some(name)
+            );
+        }
+    }
+
+    #[test]
+    fn unrelated_path_is_rejected() {
+        let mut recognizer = recognizer();
+        let square = drawn(
+            &[(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0), (0.0, 0.0)],
+            1.0,
+            (0.0, 0.0),
+            0.0,
+        );
+        assert!(recognize(&mut recognizer, &square).is_none());
+    }
+
+    #[test]
+    fn short_path_is_not_matched() {
+        let mut recognizer = recognizer();
+        let tiny = drawn(
+            &[(0.0, 0.0), (100.0, 0.0), (0.0, 100.0), (100.0, 100.0)],
+            0.1,
+            (0.0, 0.0),
+            0.0,
+        );
+        assert!(recognize(&mut recognizer, &tiny).is_none());
+    }
+
+    #[test]
+    fn degenerate_templates_are_rejected_and_names_replaced() {
+        let mut recognizer = recognizer();
+        assert!(!recognizer.add_template(template("dot", &[(5.0, 5.0), (5.0, 5.0)])));
+        assert!(!recognizer.add_template(template("single", &[(5.0, 5.0)])));
+
+        assert!(recognizer.add_template(template("z", &[(0.0, 0.0), (100.0, 0.0)])));
+        let names: Vec<&str> = recognizer
+            .templates()
+            .map(|template| template.name.as_str())
+            .collect();
+        assert_eq!(names, ["check", "caret", "z"]);
+
+        assert!(recognizer.remove_template("z"));
+        assert!(!recognizer.remove_template("z"));
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn read_templates_adds_all_templates() {
+        let mut recognizer = TemplateRecognizer::new(TemplateConfig::default());
+        let json = r#"[
+            {"name": "line", "points": [{"x": 0, "y": 0}, {"x": 100, "y": 0}]},
+            {"name": "caret", "points": [
+                {"x": 0, "y": 100}, {"x": 50, "y": 0}, {"x": 100, "y": 100}
+            ]}
+        ]"#;
+        assert_eq!(recognizer.read_templates(json.as_bytes()).unwrap(), 2);
+
+        let path = drawn(&[(0.0, 100.0), (50.0, 0.0), (100.0, 100.0)], 1.0, (0.0, 0.0), 0.0);
+        assert_eq!(
+            matched_name(recognize(&mut recognizer, &path)).as_deref(),
+            Some("caret")
+        );
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn read_templates_rejects_degenerate_template() {
+        let mut recognizer = TemplateRecognizer::new(TemplateConfig::default());
+        let json = r#"[
+            {"name": "line", "points": [{"x": 0, "y": 0}, {"x": 100, "y": 0}]},
+            {"name": "dot", "points": [{"x": 5, "y": 5}]}
+        ]"#;
+        let err = recognizer.read_templates(json.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("\"dot\""));
+        assert_eq!(recognizer.templates().count(), 0);
+
+        assert!(recognizer.read_templates("not json".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use std::io::{self, BufRead, BufWriter, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::Clock;
+use crate::model::drag_types::DragData;
+use crate::model::pointer_event::PointerEvent;
+
+/// 轨迹文件格式版本，格式不兼容地变化时递增
+pub const TRACE_VERSION: u32 = 1;
+
+/// 轨迹文件中的一行记录
+///
+/// 文件为 JSON Lines 格式，第一行是 `Header`，之后按时间顺序记录交给状态机的指针事件
+/// 以及回调读取到的拖拽数据快照。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TraceRecord {
+    /// 文件头
+    Header { version: u32 },
+    /// 指针事件及其到达时粘贴板的变化计数
+    ///
+    /// `elapsed_ms` 按实例的时钟计算，与事件不带时间戳时状态机使用的时间一致。
+    Pointer {
+        elapsed_ms: f64,
+        change_count: i64,
+        event: PointerEvent,
+    },
+    /// 回调读取到的拖拽数据，`change_count` 为触发读取的事件到达时的变化计数
+    Data {
+        elapsed_ms: f64,
+        change_count: i64,
+        data: Option<DragData>,
+    },
+}
+
+// 正在进行的录制，由各个实例分别持有
+pub(crate) struct TraceRecorder {
+    writer: BufWriter<Box<dyn Write + Send>>,
+    // 实例的时钟，事件不带时间戳时状态机使用同一个时钟，回放时据此补上时间戳
+    clock: Arc<dyn Clock>,
+    started_at: Duration,
+    // 第一次写入失败的错误，出错后不再继续写入
+    error: Option<io::Error>,
+}
+
+impl TraceRecorder {
+    // 创建录制器并写入文件头
+    pub(crate) fn new(
+        writer: impl Write + Send + 'static,
+        clock: Arc<dyn Clock>,
+    ) -> io::Result<Self> {
+        let mut recorder = Self {
+            writer: BufWriter::new(Box::new(writer)),
+            started_at: clock.now(),
+            clock,
+            error: None,
+        };
+        recorder.write(&TraceRecord::Header {
+            version: TRACE_VERSION,
+        });
+        match recorder.error.take() {
+            Some(err) => Err(err),
+            None => Ok(recorder),
+        }
+    }
+
+    // 结束录制并刷新缓冲，返回录制过程中遇到的第一个写入错误
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        self.writer.flush()
+    }
+
+    // 记录一个指针事件
+    pub(crate) fn record_pointer(&mut self, event: &PointerEvent, change_count: i64) {
+        let elapsed_ms = self.elapsed_ms();
+        self.write(&TraceRecord::Pointer {
+            elapsed_ms,
+            change_count,
+            event: *event,
+        });
+    }
+
+    // 记录一次拖拽数据快照
+    pub(crate) fn record_data(&mut self, change_count: i64, data: &Option<DragData>) {
+        let elapsed_ms = self.elapsed_ms();
+        self.write(&TraceRecord::Data {
+            elapsed_ms,
+            change_count,
+            data: data.clone(),
+        });
+    }
+
+    fn write(&mut self, record: &TraceRecord) {
+        if self.error.is_some() {
+            return;
+        }
+        let result = serde_json::to_writer(&mut self.writer, record)
+            .map_err(io::Error::from)
+            .and_then(|_| self.writer.write_all(b"\n"));
+        if let Err(err) = result {
+            self.error = Some(err);
+        }
+    }
+
+    fn elapsed_ms(&self) -> f64 {
+        self.clock
+            .now()
+            .saturating_sub(self.started_at)
+            .as_secs_f64()
+            * 1000.0
+    }
+}
+
+/// 开始录制默认实例，之后的指针事件和拖拽数据都会写入 `writer`，已有的录制会被直接替换
+///
+/// 其他实例使用 [`Dorazu::start_recording`](crate::Dorazu::start_recording) 分别录制。
+pub fn start_recording(writer: impl Write + Send + 'static) -> io::Result<()> {
+    crate::default_instance().start_recording(writer)
+}
+
+/// 停止录制默认实例并刷新缓冲，返回录制过程中遇到的第一个写入错误
+pub fn stop_recording() -> io::Result<()> {
+    crate::default_instance().stop_recording()
+}
+
+/// 默认实例是否正在录制
+pub fn is_recording() -> bool {
+    crate::default_instance().is_recording()
+}
+
+/// 读取轨迹文件中的全部记录，并校验文件头的版本
+pub fn read_trace(reader: impl BufRead) -> io::Result<Vec<TraceRecord>> {
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str::<TraceRecord>(&line)?);
+    }
+
+    match records.first() {
+        Some(TraceRecord::Header { version }) if *version == TRACE_VERSION => Ok(records),
+        Some(TraceRecord::Header { version }) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported trace version {version}, expected {TRACE_VERSION}"),
+        )),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing trace header",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::Dorazu;
+    use crate::event_queue::EventDelivery;
+    use crate::mock::{MockPasteboard, MockPointerBackend};
+
+    // 可以在录制结束后取出内容的共享缓冲区
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn instance(pasteboard: &MockPasteboard) -> Dorazu {
+        Dorazu::builder()
+            .pasteboard(Box::new(pasteboard.clone()))
+            .event_delivery(EventDelivery::Synchronous)
+            .build()
+    }
+
+    // 按下、写入数据、拖动并抬起
+    fn drag(backend: &MockPointerBackend, pasteboard: &MockPasteboard, x: f64, text: &str) {
+        backend.press(x, 0.0);
+        pasteboard.set_data(Some(DragData::PlainText(text.to_owned())));
+        backend.drag_to(x + 10.0, 0.0);
+        backend.release(x + 10.0, 0.0);
+    }
+
+    #[test]
+    fn recording_round_trips_and_only_covers_its_instance() {
+        let (recorded_pasteboard, other_pasteboard) =
+            (MockPasteboard::new(), MockPasteboard::new());
+        let (recorded, other) = (instance(&recorded_pasteboard), instance(&other_pasteboard));
+        let (recorded_backend, other_backend) =
+            (MockPointerBackend::new(), MockPointerBackend::new());
+        let _recorded_listener = recorded
+            .start_listening_with(recorded_backend.clone())
+            .unwrap();
+        let _other_listener = other.start_listening_with(other_backend.clone()).unwrap();
+
+        let buffer = SharedBuffer::default();
+        recorded.start_recording(buffer.clone()).unwrap();
+        assert!(recorded.is_recording());
+        assert!(!other.is_recording());
+        drag(&recorded_backend, &recorded_pasteboard, 0.0, "recorded");
+        drag(&other_backend, &other_pasteboard, 500.0, "other");
+        recorded.stop_recording().unwrap();
+        assert!(!recorded.is_recording());
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let records = read_trace(Cursor::new(bytes)).unwrap();
+        assert!(matches!(
+            records[0],
+            TraceRecord::Header {
+                version: TRACE_VERSION
+            }
+        ));
+
+        let pointers: Vec<(i64, PointerEvent)> = records
+            .iter()
+            .filter_map(|record| match record {
+                TraceRecord::Pointer {
+                    change_count,
+                    event,
+                    ..
+                } => Some((*change_count, *event)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(pointers.len(), 3);
+        assert!(matches!(pointers[0], (0, PointerEvent::Down { .. })));
+        assert!(matches!(pointers[1], (1, PointerEvent::Dragged { .. })));
+        assert!(matches!(pointers[2], (1, PointerEvent::Up { .. })));
+        assert!(pointers.iter().all(|(_, event)| event.location().x < 500.0));
+
+        let snapshots: Vec<_> = records
+            .iter()
+            .filter_map(|record| match record {
+                TraceRecord::Data {
+                    change_count, data, ..
+                } => Some((*change_count, data.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(snapshots.len(), 1);
+        assert!(
+            matches!(&snapshots[0], (1, Some(DragData::PlainText(text))) if text == "recorded")
+        );
+
+        let elapsed: Vec<f64> = records
+            .iter()
+            .filter_map(|record| match record {
+                TraceRecord::Pointer { elapsed_ms, .. } | TraceRecord::Data { elapsed_ms, .. } => {
+                    Some(*elapsed_ms)
+                }
+                TraceRecord::Header { .. } => None,
+            })
+            .collect();
+        assert!(elapsed.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn read_trace_checks_header_version() {
+        let future = format!(
+            "{{\"kind\":\"header\",\"version\":{}}}\n",
+            TRACE_VERSION + 1
+        );
+        let err = read_trace(Cursor::new(future)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("unsupported trace version"));
+
+        let headerless = r#"{"kind":"data","elapsed_ms":0.0,"change_count":1,"data":null}"#;
+        let err = read_trace(Cursor::new(headerless)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        assert!(read_trace(Cursor::new("")).is_err());
+        assert!(read_trace(Cursor::new("not json\n")).is_err());
+
+        let current = format!("{{\"kind\":\"header\",\"version\":{TRACE_VERSION}}}\n\n");
+        assert_eq!(read_trace(Cursor::new(current)).unwrap().len(), 1);
+    }
+}
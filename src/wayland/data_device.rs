@@ -0,0 +1,453 @@
+use std::error::Error;
+use std::io::{self, PipeReader, Read};
+use std::os::fd::{AsFd, AsRawFd};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use wayland_client::globals::{GlobalListContents, registry_queue_init};
+use wayland_client::protocol::wl_callback::WlCallback;
+use wayland_client::protocol::wl_data_device::{self, WlDataDevice};
+use wayland_client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use wayland_client::protocol::wl_data_offer::{self, WlDataOffer};
+use wayland_client::protocol::wl_registry::WlRegistry;
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::{Connection, Dispatch, EventQueue, Proxy, QueueHandle, event_created_child};
+
+use crate::backend::{
+    ListenerError, ListenerHandle, PointerBackend, PointerEventCallback, StopSignal, ThreadListener,
+};
+use crate::mime_data::{MimeKind, decode_text, resolve_drag_data};
+use crate::model::drag_types::DragData;
+use crate::model::pointer_event::{MouseButton, Point, PointerEvent};
+use crate::pasteboard::DragPasteboard;
+
+// 读取一次拖放数据时等待拖拽源写完数据的总时长
+const RECEIVE_TIMEOUT: Duration = Duration::from_millis(500);
+
+// 拖放数据提供的 MIME 类型列表，作为 wl_data_offer 的用户数据
+type OfferMimeTypes = Mutex<Vec<String>>;
+
+/// 基于 `wl_data_device` 的 Wayland 后端
+///
+/// Wayland 不允许客户端观察全局指针，合成器只会把经过本客户端表面的拖放
+/// 通过 `wl_data_device` 通知出来。因此该后端只能感知拖到应用自己窗口上的拖拽，
+/// 坐标也是相对于所在表面的。若应用已经有自己的 Wayland 连接，应通过
+/// [`WaylandBackend::from_connection`] 共享它，这样拖过应用窗口时才能收到事件。
+///
+/// 拖放进入表面时依次上报按下和拖动事件，离开或放下时上报抬起事件。
+/// 拖放数据在进入表面时就读取，事件被处理得晚、拖放已经离开时也能读到本次拖放的数据。
+/// 拖放数据通过 [`WaylandBackend::pasteboard`] 提供，开始监听时它会自动替换实例当前使用的粘贴板。
+///
+/// 事件的时间戳以后端创建时刻为起点。合成器只为移动事件提供时间，进入时的事件以到达时刻为准，
+/// 之后的移动事件按合成器时间的间隔换算到同一起点上，放下和离开时沿用最近一次的时间。
+/// 事件分发运行在后台线程中。
+pub struct WaylandBackend {
+    conn: Connection,
+    shared: Arc<Mutex<Shared>>,
+    origin: Instant,
+}
+
+// 后端与粘贴板共享的拖放状态
+#[derive(Default)]
+struct Shared {
+    change_count: i64,
+    offer: Option<WlDataOffer>,
+    // 最近一次拖放进入时读取的数据，保留到下一次拖放进入
+    data: Option<DragData>,
+    location: Point,
+}
+
+impl WaylandBackend {
+    /// 按 `WAYLAND_DISPLAY` 环境变量连接合成器
+    pub fn connect() -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self::from_connection(Connection::connect_to_env()?))
+    }
+
+    /// 复用已有的 Wayland 连接
+    pub fn from_connection(conn: Connection) -> Self {
+        Self {
+            conn,
+            shared: Arc::new(Mutex::new(Shared::default())),
+            origin: Instant::now(),
+        }
+    }
+
+    /// 获取与该后端共享拖放状态的粘贴板
+    pub fn pasteboard(&self) -> WaylandPasteboard {
+        WaylandPasteboard {
+            shared: self.shared.clone(),
+        }
+    }
+
+    // 绑定数据设备，返回事件队列
+    fn bind(
+        &self,
+    ) -> Result<(EventQueue<DeviceState>, WlDataDevice), Box<dyn Error + Send + Sync>> {
+        let (globals, event_queue) = registry_queue_init::<DeviceState>(&self.conn)?;
+        let qh = event_queue.handle();
+
+        let seat: WlSeat = globals.bind(&qh, 1..=1, ())?;
+        let manager: WlDataDeviceManager = globals.bind(&qh, 1..=3, ())?;
+        let data_device = manager.get_data_device(&seat, &qh, ());
+        Ok((event_queue, data_device))
+    }
+}
+
+impl PointerBackend for WaylandBackend {
+    fn start_listening(
+        &mut self,
+        callback: PointerEventCallback,
+    ) -> Result<ListenerHandle, ListenerError> {
+        let (mut event_queue, data_device) = self.bind()?;
+        let qh = event_queue.handle();
+        let signal = Arc::new(StopSignal::default());
+
+        {
+            let signal = signal.clone();
+            let mut state = DeviceState {
+                callback,
+                shared: self.shared.clone(),
+                dropped: false,
+                origin: self.origin,
+                motion_anchor: None,
+                last_time: Duration::ZERO,
+            };
+            thread::spawn(move || {
+                // 分发事件直到停止或连接断开
+                while !signal.is_stopped() {
+                    if event_queue.blocking_dispatch(&mut state).is_err() {
+                        break;
+                    }
+                }
+                if data_device.version() >= 2 {
+                    data_device.release();
+                }
+                signal.stop();
+            });
+        }
+
+        let conn = self.conn.clone();
+        Ok(ListenerHandle::new(ThreadListener::new(
+            signal,
+            move || {
+                // 发起一次往返，合成器的回应会唤醒阻塞中的事件分发
+                conn.display().sync(&qh, ());
+                let _ = conn.flush();
+            },
+        )))
+    }
+
+    fn current_location(&self) -> Point {
+        self.shared.lock().unwrap().location
+    }
+
+    fn drag_pasteboard(&self) -> Option<Box<dyn DragPasteboard>> {
+        Some(Box::new(self.pasteboard()))
+    }
+}
+
+/// 提供当前 Wayland 拖放数据的粘贴板，由 [`WaylandBackend::pasteboard`] 创建
+///
+/// 每次拖放进入表面时变化计数加一，数据为进入时读取的内容。
+pub struct WaylandPasteboard {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl DragPasteboard for WaylandPasteboard {
+    fn change_count(&self) -> i64 {
+        self.shared.lock().unwrap().change_count
+    }
+
+    fn data(&self) -> Option<DragData> {
+        self.shared.lock().unwrap().data.clone()
+    }
+}
+
+// 读取拖放数据提议中的内容，所有类型的读取共用一个截止时间
+fn read_offer(conn: &Connection, offer: &WlDataOffer) -> Option<DragData> {
+    let mime_types = offer.data::<OfferMimeTypes>()?.lock().unwrap().clone();
+    let deadline = Instant::now() + RECEIVE_TIMEOUT;
+
+    let read_first = |candidates: &[&str]| {
+        candidates
+            .iter()
+            .filter(|candidate| mime_types.iter().any(|mime| mime == *candidate))
+            .find_map(|mime| receive(conn, offer, mime, deadline))
+    };
+    resolve_drag_data(|kind| match kind {
+        MimeKind::UriList => read_first(&["text/uri-list"]),
+        MimeKind::Html => read_first(&["text/html"]),
+        MimeKind::PlainText => read_first(&[
+            "text/plain;charset=utf-8",
+            "UTF8_STRING",
+            "text/plain",
+            "TEXT",
+            "STRING",
+        ]),
+    })
+}
+
+// 请求拖拽源以指定 MIME 类型写入管道并读取内容
+fn receive(
+    conn: &Connection,
+    offer: &WlDataOffer,
+    mime_type: &str,
+    deadline: Instant,
+) -> Option<String> {
+    let (reader, writer) = io::pipe().ok()?;
+    offer.receive(mime_type.to_owned(), writer.as_fd());
+    conn.flush().ok()?;
+    // 关闭本端的写入端，拖拽源写完并关闭后读取才会结束
+    drop(writer);
+
+    let bytes = read_until(reader, deadline)?;
+    Some(decode_text(&bytes))
+}
+
+// 在截止时间前读完管道中的内容，拖拽源迟迟不关闭写入端时放弃，读取端随之关闭
+fn read_until(mut reader: PipeReader, deadline: Instant) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return None;
+        }
+        let mut fd = libc::pollfd {
+            fd: reader.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = remaining.as_millis().clamp(1, libc::c_int::MAX as u128) as libc::c_int;
+        match unsafe { libc::poll(&mut fd, 1, timeout) } {
+            0 => return None,
+            result if result < 0 => {
+                if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+                    return None;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        // 可读或写入端已关闭，此时读取不会阻塞
+        match reader.read(&mut buffer) {
+            Ok(0) => return Some(bytes),
+            Ok(count) => bytes.extend_from_slice(&buffer[..count]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => return None,
+        }
+    }
+}
+
+// 事件分发状态
+struct DeviceState {
+    callback: PointerEventCallback,
+    shared: Arc<Mutex<Shared>>,
+    // 本次拖放是否已经放下，放下后的离开事件不再重复上报抬起
+    dropped: bool,
+    // 时间戳的起点
+    origin: Instant,
+    // 本次拖放第一个移动事件的合成器时间（毫秒）及换算后的时间戳，离开时清除
+    motion_anchor: Option<(u32, Duration)>,
+    // 本次拖放最近一个事件的时间戳，放下和离开事件不带时间，沿用该时间
+    last_time: Duration,
+}
+
+impl DeviceState {
+    // 更新位置并上报事件，上报时不持有共享状态的锁
+    fn emit(
+        &self,
+        make_event: fn(MouseButton, Point, Option<Duration>) -> PointerEvent,
+        x: f64,
+        y: f64,
+    ) {
+        let location = Point { x, y };
+        self.shared.lock().unwrap().location = location;
+        (self.callback)(make_event(
+            MouseButton::Left,
+            location,
+            Some(self.last_time),
+        ));
+    }
+
+    // 开始新的拖放，进入事件不带合成器时间，以到达时刻为准
+    fn begin(&mut self) {
+        self.dropped = false;
+        self.motion_anchor = None;
+        self.last_time = self.origin.elapsed();
+    }
+
+    // 把合成器时间换算到后端的起点上，以本次拖放第一个移动事件为基准，不混用两种时间
+    fn motion_time(&mut self, time: u32) -> Duration {
+        let (anchor_time, anchor) = *self
+            .motion_anchor
+            .get_or_insert_with(|| (time, self.origin.elapsed()));
+        // 合成器时间以毫秒为单位，约 49 天回绕一次
+        anchor + Duration::from_millis(time.wrapping_sub(anchor_time).into())
+    }
+
+    // 结束本次拖放并释放拖放数据
+    fn finish(&mut self) {
+        if !self.dropped {
+            let location = self.shared.lock().unwrap().location;
+            self.emit(
+                |button, location, timestamp| PointerEvent::Up {
+                    button,
+                    location,
+                    timestamp,
+                },
+                location.x,
+                location.y,
+            );
+        }
+        self.dropped = false;
+        self.motion_anchor = None;
+        if let Some(offer) = self.shared.lock().unwrap().offer.take() {
+            offer.destroy();
+        }
+    }
+}
+
+impl Dispatch<WlDataDevice, ()> for DeviceState {
+    fn event(
+        state: &mut Self,
+        _proxy: &WlDataDevice,
+        event: wl_data_device::Event,
+        _data: &(),
+        conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_data_device::Event::Enter { x, y, id, .. } => {
+                // 变化计数在回调被调用时读取，先上报按下再更新计数，
+                // 使状态机无论同步还是排队处理都记录拖拽开始前的计数
+                state.begin();
+                state.emit(
+                    |button, location, timestamp| PointerEvent::Down {
+                        button,
+                        location,
+                        timestamp,
+                    },
+                    x,
+                    y,
+                );
+                // 数据提议在离开时就会被销毁，在此读取数据，之后的处理不依赖提议是否仍然有效
+                let data = id.as_ref().and_then(|offer| read_offer(conn, offer));
+                {
+                    let mut shared = state.shared.lock().unwrap();
+                    if let Some(previous) = std::mem::replace(&mut shared.offer, id) {
+                        previous.destroy();
+                    }
+                    shared.data = data;
+                    shared.change_count += 1;
+                }
+                state.emit(
+                    |button, location, timestamp| PointerEvent::Dragged {
+                        button,
+                        location,
+                        timestamp,
+                    },
+                    x,
+                    y,
+                );
+            }
+            wl_data_device::Event::Motion { time, x, y } => {
+                state.last_time = state.motion_time(time);
+                state.emit(
+                    |button, location, timestamp| PointerEvent::Dragged {
+                        button,
+                        location,
+                        timestamp,
+                    },
+                    x,
+                    y,
+                );
+            }
+            wl_data_device::Event::Drop => {
+                let location = state.shared.lock().unwrap().location;
+                state.emit(
+                    |button, location, timestamp| PointerEvent::Up {
+                        button,
+                        location,
+                        timestamp,
+                    },
+                    location.x,
+                    location.y,
+                );
+                state.dropped = true;
+            }
+            wl_data_device::Event::Leave => state.finish(),
+            // 剪贴板选区与拖放无关，直接释放
+            wl_data_device::Event::Selection { id: Some(offer) } => offer.destroy(),
+            _ => {}
+        }
+    }
+
+    event_created_child!(DeviceState, WlDataDevice, [
+        wl_data_device::EVT_DATA_OFFER_OPCODE => (WlDataOffer, OfferMimeTypes::default()),
+    ]);
+}
+
+impl Dispatch<WlDataOffer, OfferMimeTypes> for DeviceState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlDataOffer,
+        event: wl_data_offer::Event,
+        data: &OfferMimeTypes,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wl_data_offer::Event::Offer { mime_type } = event {
+            data.lock().unwrap().push(mime_type);
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for DeviceState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlRegistry,
+        _event: <WlRegistry as Proxy>::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlCallback, ()> for DeviceState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlCallback,
+        _event: <WlCallback as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlSeat, ()> for DeviceState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlSeat,
+        _event: <WlSeat as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlDataDeviceManager, ()> for DeviceState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlDataDeviceManager,
+        _event: <WlDataDeviceManager as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
@@ -0,0 +1,2 @@
+pub mod pointer_listener;
+pub mod xdnd_selection;
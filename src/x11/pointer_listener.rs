@@ -0,0 +1,241 @@
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::Event;
+use x11rb::protocol::xinput::{self, ConnectionExt as _, Device, EventMask, XIEventMask};
+use x11rb::protocol::xproto::{
+    AtomEnum, ClientMessageEvent, ConnectionExt as _, CreateWindowAux, EventMask as XEventMask,
+    Timestamp, Window, WindowClass,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::{COPY_DEPTH_FROM_PARENT, NONE};
+
+use crate::backend::{
+    ListenerError, ListenerHandle, PointerBackend, PointerEventCallback, StopSignal, ThreadListener,
+};
+use crate::model::pointer_event::{MouseButton, Point, PointerEvent};
+
+/// 基于 XInput2 原始事件的 X11 指针后端
+///
+/// 在根窗口上监听所有主设备的 RawButtonPress/RawButtonRelease/RawMotion 事件，
+/// 原始事件不受其他客户端抓取指针的影响，因此拖拽过程中也能持续收到。
+/// 事件循环运行在后台线程中。
+#[derive(Debug, Default)]
+pub struct X11PointerBackend {
+    display: Option<String>,
+    location: Arc<Mutex<Point>>,
+}
+
+// 已完成初始化的 X11 连接
+struct X11Session {
+    conn: RustConnection,
+    root: Window,
+    // 用于停止时唤醒事件循环的隐藏窗口
+    wake_window: Window,
+}
+
+impl X11PointerBackend {
+    /// 连接 `DISPLAY` 环境变量指定的显示
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 连接指定名称的显示，例如 `:99`
+    pub fn with_display(display: &str) -> Self {
+        Self {
+            display: Some(display.to_owned()),
+            ..Self::default()
+        }
+    }
+
+    // 建立连接并订阅原始指针事件
+    fn connect(&self) -> Result<X11Session, Box<dyn Error + Send + Sync>> {
+        let (conn, screen_num) = x11rb::connect(self.display.as_deref())?;
+        let root = conn.setup().roots[screen_num].root;
+
+        if conn
+            .extension_information(xinput::X11_EXTENSION_NAME)?
+            .is_none()
+        {
+            return Err("X server does not support XInputExtension".into());
+        }
+        // 原始事件始终投递到根窗口需要 XInput 2.1 及以上
+        let version = conn.xinput_xi_query_version(2, 2)?.reply()?;
+        if (version.major_version, version.minor_version) < (2, 1) {
+            return Err(format!(
+                "XInput {}.{} is too old, 2.1 or newer is required",
+                version.major_version, version.minor_version
+            )
+            .into());
+        }
+
+        conn.xinput_xi_select_events(
+            root,
+            &[EventMask {
+                deviceid: Device::ALL_MASTER.into(),
+                mask: vec![
+                    XIEventMask::RAW_BUTTON_PRESS
+                        | XIEventMask::RAW_BUTTON_RELEASE
+                        | XIEventMask::RAW_MOTION,
+                ],
+            }],
+        )?;
+
+        let wake_window = conn.generate_id()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            wake_window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            NONE,
+            &CreateWindowAux::new(),
+        )?;
+        conn.flush()?;
+
+        Ok(X11Session {
+            conn,
+            root,
+            wake_window,
+        })
+    }
+}
+
+impl PointerBackend for X11PointerBackend {
+    fn start_listening(
+        &mut self,
+        callback: PointerEventCallback,
+    ) -> Result<ListenerHandle, ListenerError> {
+        let session = Arc::new(self.connect()?);
+        let signal = Arc::new(StopSignal::default());
+
+        {
+            let session = session.clone();
+            let signal = signal.clone();
+            let location = self.location.clone();
+            thread::spawn(move || {
+                // 连接断开等错误会结束监听
+                let _ = run_event_loop(&session, &callback, &location, &signal);
+                signal.stop();
+            });
+        }
+
+        Ok(ListenerHandle::new(ThreadListener::new(
+            signal,
+            move || {
+                // 向隐藏窗口发送一条消息，使阻塞中的 wait_for_event 返回
+                let event =
+                    ClientMessageEvent::new(32, session.wake_window, AtomEnum::NONE, [0u32; 5]);
+                let _ = session.conn.send_event(
+                    false,
+                    session.wake_window,
+                    XEventMask::NO_EVENT,
+                    event,
+                );
+                let _ = session.conn.flush();
+            },
+        )))
+    }
+
+    fn current_location(&self) -> Point {
+        *self.location.lock().unwrap()
+    }
+}
+
+// 事件循环，直到停止或连接断开
+fn run_event_loop(
+    session: &X11Session,
+    callback: &PointerEventCallback,
+    location: &Mutex<Point>,
+    signal: &StopSignal,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let conn = &session.conn;
+    let root = session.root;
+    // 当前按下的按键，拖动事件以最先按下的按键上报
+    let mut pressed: Option<MouseButton> = None;
+
+    while !signal.is_stopped() {
+        let event = match conn.wait_for_event()? {
+            Event::XinputRawButtonPress(event) => {
+                let Some(button) = to_mouse_button(event.detail) else {
+                    continue;
+                };
+                pressed.get_or_insert(button);
+                let location = query_location(conn, root, location)?;
+                PointerEvent::Down {
+                    button,
+                    location,
+                    timestamp: Some(to_duration(event.time)),
+                }
+            }
+            Event::XinputRawButtonRelease(event) => {
+                let Some(button) = to_mouse_button(event.detail) else {
+                    continue;
+                };
+                if pressed == Some(button) {
+                    pressed = None;
+                }
+                let location = query_location(conn, root, location)?;
+                PointerEvent::Up {
+                    button,
+                    location,
+                    timestamp: Some(to_duration(event.time)),
+                }
+            }
+            Event::XinputRawMotion(event) => {
+                let location = query_location(conn, root, location)?;
+                match pressed {
+                    Some(button) => PointerEvent::Dragged {
+                        button,
+                        location,
+                        timestamp: Some(to_duration(event.time)),
+                    },
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+        callback(event);
+    }
+
+    conn.destroy_window(session.wake_window)?;
+    conn.flush()?;
+    Ok(())
+}
+
+// 原始事件不携带坐标，通过 QueryPointer 获取指针在根窗口中的位置
+fn query_location(
+    conn: &impl Connection,
+    root: Window,
+    location: &Mutex<Point>,
+) -> Result<Point, Box<dyn Error + Send + Sync>> {
+    let reply = conn.query_pointer(root)?.reply()?;
+    let point = Point {
+        x: reply.root_x as f64,
+        y: reply.root_y as f64,
+    };
+    *location.lock().unwrap() = point;
+    Ok(point)
+}
+
+// X 服务器时间戳以毫秒为单位
+fn to_duration(time: Timestamp) -> Duration {
+    Duration::from_millis(time.into())
+}
+
+// 将 X11 按键编号转换为鼠标按键，滚轮（4~7）不视为按键
+fn to_mouse_button(detail: u32) -> Option<MouseButton> {
+    match detail {
+        1 => Some(MouseButton::Left),
+        3 => Some(MouseButton::Right),
+        4..=7 => None,
+        other => Some(MouseButton::Other(other)),
+    }
+}
@@ -0,0 +1,275 @@
+use std::error::Error;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::Event;
+use x11rb::protocol::xfixes::{self, ConnectionExt as _, SelectionEventMask};
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, GetPropertyReply, Window, WindowClass,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::{COPY_DEPTH_FROM_PARENT, CURRENT_TIME};
+
+use crate::mime_data::{MimeKind, decode_text, resolve_drag_data};
+use crate::model::drag_types::DragData;
+use crate::pasteboard::DragPasteboard;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        XdndSelection,
+        TARGETS,
+        INCR,
+        UTF8_STRING,
+        TEXT_URI_LIST: b"text/uri-list",
+        TEXT_HTML: b"text/html",
+        TEXT_PLAIN: b"text/plain",
+        TEXT_PLAIN_UTF8: b"text/plain;charset=utf-8",
+        DORAZU_XDND: b"_DORAZU_XDND",
+    }
+}
+
+// 读取一次拖拽数据时等待拖拽源响应选区转换的总时长
+const CONVERT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 基于 XDND 选区（`XdndSelection`）的拖拽粘贴板
+///
+/// 每次选区所有者被设置（即开始一次新的拖拽）时变化计数加一，
+/// 读取数据时向当前所有者请求转换并从隐藏窗口的属性中取回内容。
+/// 暂不支持 INCR 分段传输，过大的内容会被视为不可用。
+///
+/// 选区通知和数据转换使用两个连接，读取数据时不会阻塞或打乱其他线程对变化计数的读取。
+pub struct XdndPasteboard {
+    // 接收选区所有者变化通知的连接和窗口
+    conn: RustConnection,
+    window: Window,
+    // 读取数据用的连接，同一时间只进行一次转换
+    converter: Mutex<Converter>,
+    atoms: Atoms,
+    change_count: AtomicI64,
+}
+
+// 请求选区转换的连接和接收转换结果的隐藏窗口
+struct Converter {
+    conn: RustConnection,
+    window: Window,
+}
+
+impl XdndPasteboard {
+    /// 连接 `DISPLAY` 环境变量指定的显示
+    pub fn new() -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Self::connect(None)
+    }
+
+    /// 连接指定名称的显示，为 `None` 时使用 `DISPLAY` 环境变量
+    pub fn connect(display: Option<&str>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let (conn, window) = open_hidden_window(display)?;
+        let atoms = Atoms::new(&conn)?.reply()?;
+
+        if conn
+            .extension_information(xfixes::X11_EXTENSION_NAME)?
+            .is_none()
+        {
+            return Err("X server does not support XFIXES".into());
+        }
+        conn.xfixes_query_version(5, 0)?.reply()?;
+        conn.xfixes_select_selection_input(
+            window,
+            atoms.XdndSelection,
+            SelectionEventMask::SET_SELECTION_OWNER,
+        )?;
+        conn.flush()?;
+
+        let (converter_conn, converter_window) = open_hidden_window(display)?;
+        converter_conn.flush()?;
+
+        Ok(Self {
+            conn,
+            window,
+            converter: Mutex::new(Converter {
+                conn: converter_conn,
+                window: converter_window,
+            }),
+            atoms,
+            change_count: AtomicI64::new(0),
+        })
+    }
+
+    // 处理一个事件，选区所有者被重新设置时递增计数
+    fn handle_event(&self, event: &Event) {
+        if let Event::XfixesSelectionNotify(event) = event
+            && event.selection == self.atoms.XdndSelection
+        {
+            self.change_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // 处理所有已到达的事件
+    fn drain_events(&self) {
+        while let Ok(Some(event)) = self.conn.poll_for_event() {
+            self.handle_event(&event);
+        }
+    }
+
+    // 请求将 XDND 选区转换为指定目标类型并读取内容
+    fn convert(
+        &self,
+        converter: &Converter,
+        target: Atom,
+        deadline: Instant,
+    ) -> Option<GetPropertyReply> {
+        if Instant::now() >= deadline {
+            return None;
+        }
+        let property = self.atoms.DORAZU_XDND;
+        converter
+            .conn
+            .convert_selection(
+                converter.window,
+                self.atoms.XdndSelection,
+                target,
+                property,
+                CURRENT_TIME,
+            )
+            .ok()?;
+        converter.conn.flush().ok()?;
+
+        loop {
+            match converter.conn.poll_for_event().ok()? {
+                Some(Event::SelectionNotify(event))
+                    if event.requestor == converter.window
+                        && event.selection == self.atoms.XdndSelection
+                        && event.target == target =>
+                {
+                    // property 为 NONE 表示拖拽源拒绝了该类型
+                    if event.property == x11rb::NONE {
+                        return None;
+                    }
+                    break;
+                }
+                Some(_) => {}
+                None if Instant::now() >= deadline => return None,
+                None => thread::sleep(Duration::from_millis(5)),
+            }
+        }
+
+        let reply = converter
+            .conn
+            .get_property(
+                true,
+                converter.window,
+                property,
+                AtomEnum::ANY,
+                0,
+                u32::MAX / 4,
+            )
+            .ok()?
+            .reply()
+            .ok()?;
+        if reply.type_ == self.atoms.INCR {
+            return None;
+        }
+        Some(reply)
+    }
+
+    // 获取拖拽源提供的目标类型列表
+    fn targets(&self, converter: &Converter, deadline: Instant) -> Vec<Atom> {
+        self.convert(converter, self.atoms.TARGETS, deadline)
+            .and_then(|reply| reply.value32().map(Iterator::collect))
+            .unwrap_or_default()
+    }
+
+    // 按优先级读取第一个可用的文本类型，拖拽源未提供 TARGETS 时逐个尝试
+    fn read_text(
+        &self,
+        converter: &Converter,
+        targets: &[Atom],
+        candidates: &[Atom],
+        deadline: Instant,
+    ) -> Option<String> {
+        candidates
+            .iter()
+            .filter(|target| targets.is_empty() || targets.contains(target))
+            .find_map(|&target| self.convert(converter, target, deadline))
+            .map(|reply| decode_text(&reply.value))
+    }
+}
+
+impl DragPasteboard for XdndPasteboard {
+    fn change_count(&self) -> i64 {
+        self.drain_events();
+        self.change_count.load(Ordering::Relaxed)
+    }
+
+    fn data(&self) -> Option<DragData> {
+        let converter = self.converter.lock().unwrap();
+        let owner = converter
+            .conn
+            .get_selection_owner(self.atoms.XdndSelection)
+            .ok()?
+            .reply()
+            .ok()?
+            .owner;
+        if owner == x11rb::NONE {
+            return None;
+        }
+
+        // 所有目标类型的转换共用一个截止时间，拖拽源不响应时也不会逐个等待
+        let deadline = Instant::now() + CONVERT_TIMEOUT;
+        let targets = self.targets(&converter, deadline);
+        resolve_drag_data(|kind| match kind {
+            MimeKind::UriList => {
+                self.read_text(&converter, &targets, &[self.atoms.TEXT_URI_LIST], deadline)
+            }
+            MimeKind::Html => {
+                self.read_text(&converter, &targets, &[self.atoms.TEXT_HTML], deadline)
+            }
+            MimeKind::PlainText => self.read_text(
+                &converter,
+                &targets,
+                &[
+                    self.atoms.UTF8_STRING,
+                    self.atoms.TEXT_PLAIN_UTF8,
+                    self.atoms.TEXT_PLAIN,
+                ],
+                deadline,
+            ),
+        })
+    }
+}
+
+impl Drop for XdndPasteboard {
+    fn drop(&mut self) {
+        let _ = self.conn.destroy_window(self.window);
+        let _ = self.conn.flush();
+        if let Ok(converter) = self.converter.get_mut() {
+            let _ = converter.conn.destroy_window(converter.window);
+            let _ = converter.conn.flush();
+        }
+    }
+}
+
+// 连接显示并创建一个不映射的隐藏窗口，作为选区通知或转换结果的接收方
+fn open_hidden_window(
+    display: Option<&str>,
+) -> Result<(RustConnection, Window), Box<dyn Error + Send + Sync>> {
+    let (conn, screen_num) = x11rb::connect(display)?;
+    let root = conn.setup().roots[screen_num].root;
+    let window = conn.generate_id()?;
+    conn.create_window(
+        COPY_DEPTH_FROM_PARENT,
+        window,
+        root,
+        0,
+        0,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_ONLY,
+        x11rb::NONE,
+        &CreateWindowAux::new(),
+    )?;
+    Ok((conn, window))
+}